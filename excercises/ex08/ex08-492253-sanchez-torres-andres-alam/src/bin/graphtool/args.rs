@@ -0,0 +1,167 @@
+//! Hand-rolled command line parsing: `<command> [args] [--option value]
+//! [--flag]`, with `--option=value` and `-o`/`-h` short forms.
+use std::collections::HashMap;
+
+use crate::CliError;
+
+/// Options that take a value.
+const VALUE_OPTIONS: [&str; 7] = [
+    "format",
+    "algorithm",
+    "output",
+    "seed",
+    "weights",
+    "top",
+    "threads",
+];
+/// Options without a value.
+const FLAGS: [&str; 4] = ["directed", "help", "no-cache", "weighted"];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+pub struct Args {
+    pub command: Option<String>,
+    positional: Vec<String>,
+    options: HashMap<&'static str, String>,
+    flags: Vec<&'static str>,
+}
+
+impl Args {
+    pub fn parse(mut raw_args: impl Iterator<Item = String>) -> Result<Self, CliError> {
+        let mut args = Args {
+            command: None,
+            positional: vec![],
+            options: HashMap::new(),
+            flags: vec![],
+        };
+
+        while let Some(arg) = raw_args.next() {
+            let name = match arg.as_str() {
+                "-o" => "output".to_string(),
+                "-h" => "help".to_string(),
+                _ if arg.starts_with("--") => arg[2..].to_string(),
+                // anything else, negative numbers included, is an argument
+                _ => {
+                    match args.command {
+                        None => args.command = Some(arg),
+                        Some(_) => args.positional.push(arg),
+                    }
+                    continue;
+                }
+            };
+            let (name, inline_value) = match name.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (name, None),
+            };
+
+            if let Some(flag) = FLAGS.iter().find(|flag| **flag == name) {
+                if inline_value.is_some() {
+                    return Err(CliError::Usage(format!("--{} takes no value", name)));
+                }
+                args.flags.push(flag);
+            } else if let Some(option) = VALUE_OPTIONS.iter().find(|option| **option == name) {
+                let value = match inline_value {
+                    Some(value) => value,
+                    None => raw_args
+                        .next()
+                        .ok_or_else(|| CliError::Usage(format!("--{} needs a value", name)))?,
+                };
+                if args.options.insert(option, value).is_some() {
+                    return Err(CliError::Usage(format!("--{} given more than once", name)));
+                }
+            } else {
+                return Err(CliError::Usage(format!("unknown option --{}", name)));
+            }
+        }
+
+        Ok(args)
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(&name)
+    }
+
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    /// Fails on options the command doesn't use, so typos don't go unnoticed.
+    pub fn check_options(&self, command: &str, allowed: &[&str]) -> Result<(), CliError> {
+        let used = self.options.keys().chain(self.flags.iter());
+        for name in used {
+            if !allowed.contains(name) && !["format", "help", "directed"].contains(name) {
+                return Err(CliError::Usage(format!(
+                    "option --{} is not used by {}",
+                    name, command
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Exactly `names.len()` positional arguments, named for the error.
+    pub fn positional<const N: usize>(&self, names: [&str; N]) -> Result<[&str; N], CliError> {
+        if self.positional.len() != N {
+            return Err(CliError::Usage(format!(
+                "expected {} argument(s): {}",
+                N,
+                names.join(" ")
+            )));
+        }
+        Ok(std::array::from_fn(|index| self.positional[index].as_str()))
+    }
+
+    /// All positional arguments, for commands taking a variable count.
+    pub fn positional_all(&self) -> &[String] {
+        &self.positional
+    }
+
+    pub fn format(&self) -> Result<Format, CliError> {
+        match self.option("format") {
+            None | Some("text") => Ok(Format::Text),
+            Some("json") => Ok(Format::Json),
+            Some("csv") => Ok(Format::Csv),
+            Some(other) => Err(CliError::Usage(format!(
+                "unknown format {} (expected text, json or csv)",
+                other
+            ))),
+        }
+    }
+
+    /// The `--algorithm` value, defaulting to the first of `known`.
+    pub fn algorithm(&self, known: &[&'static str]) -> Result<&'static str, CliError> {
+        match self.option("algorithm") {
+            None => Ok(known[0]),
+            Some(name) => known
+                .iter()
+                .find(|algorithm| **algorithm == name)
+                .copied()
+                .ok_or_else(|| {
+                    CliError::Usage(format!(
+                        "unknown algorithm {} (expected one of: {})",
+                        name,
+                        known.join(", ")
+                    ))
+                }),
+        }
+    }
+
+    /// Parses a value option, `None` when absent.
+    pub fn parsed_option<V: std::str::FromStr>(&self, name: &str) -> Result<Option<V>, CliError> {
+        self.option(name)
+            .map(|value| parse_value(name, value))
+            .transpose()
+    }
+}
+
+/// Parses a user-supplied value, naming it in the error.
+pub fn parse_value<V: std::str::FromStr>(name: &str, value: &str) -> Result<V, CliError> {
+    value
+        .parse()
+        .map_err(|_| CliError::Usage(format!("invalid {}: {}", name, value)))
+}
@@ -0,0 +1,317 @@
+//! `graphtool batch`: the same shortest path queries over many graphs, one
+//! graph per worker thread, reported as a single table.
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Instant,
+};
+
+use crate::{
+    args::Args,
+    commands::{find_shortest_path, load_graph, node, node_id, Node, Report, Weight},
+    json::Json,
+    CliError,
+};
+
+/// One table row: a query on a graph, or a graph that failed to load.
+struct Row {
+    graph: String,
+    // as written in the queries file
+    source_label: String,
+    target_label: String,
+    nodes: Option<(Node, Node)>,
+    distance: Option<Weight>,
+    path: Option<Vec<Node>>,
+    // the path by node labels, for text and CSV
+    path_labels: String,
+    load_ms: f64,
+    query_ms: Option<f64>,
+    error: Option<String>,
+}
+
+/// Whether `name` matches `pattern`, where `*` stands for any run of
+/// characters and `?` for one.
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| glob_match(rest, &name[skip..])),
+        Some(('?', rest)) => !name.is_empty() && glob_match(rest, &name[1..]),
+        Some((character, rest)) => name.first() == Some(character) && glob_match(rest, &name[1..]),
+    }
+}
+
+/// The .gph files of a directory, the files matching a glob in the last
+/// path component, or a single file; sorted by path.
+fn graph_files(input: &str) -> Result<Vec<PathBuf>, CliError> {
+    let input_path = Path::new(input);
+    let (directory, pattern) = if input_path.is_dir() {
+        (input_path, "*.gph")
+    } else {
+        let file_name = input_path.file_name().and_then(OsStr::to_str).unwrap_or("");
+        if !file_name.contains(['*', '?']) {
+            return Ok(vec![input_path.to_path_buf()]);
+        }
+        let directory = input_path
+            .parent()
+            .filter(|parent| *parent != Path::new(""));
+        (directory.unwrap_or(Path::new(".")), file_name)
+    };
+
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let entries = fs::read_dir(directory)
+        .map_err(|error| CliError::Failed(format!("{}: {}", directory.display(), error)))?;
+    let mut files = vec![];
+    for entry in entries {
+        let entry = entry
+            .map_err(|error| CliError::Failed(format!("{}: {}", directory.display(), error)))?;
+        let name = entry.file_name();
+        let matches = name
+            .to_str()
+            .is_some_and(|name| glob_match(&pattern, &name.chars().collect::<Vec<_>>()));
+        if matches && entry.path().is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+
+    if files.is_empty() {
+        return Err(CliError::Failed(format!("no graphs match {}", input)));
+    }
+    Ok(files)
+}
+
+/// `<source> <target>` label pairs, one per line.
+fn read_queries(path: &str) -> Result<Vec<(String, String)>, CliError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|error| CliError::Failed(format!("{}: {}", path, error)))?;
+
+    let mut queries = vec![];
+    for (line_index, line) in contents.lines().enumerate() {
+        let line = line.split('#').next().unwrap();
+        let values = line.split_whitespace().collect::<Vec<_>>();
+        match values.as_slice() {
+            [] => continue,
+            [source, target] => queries.push((source.to_string(), target.to_string())),
+            _ => {
+                return Err(CliError::Failed(format!(
+                    "{}: line {} is not `<source> <target>`",
+                    path,
+                    line_index + 1
+                )))
+            }
+        }
+    }
+
+    if queries.is_empty() {
+        return Err(CliError::Failed(format!("{}: no queries", path)));
+    }
+    Ok(queries)
+}
+
+fn milliseconds(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+fn run_graph(args: &Args, path: &Path, queries: &[(String, String)], algorithm: &str) -> Vec<Row> {
+    let graph_name = path.display().to_string();
+    let load_start = Instant::now();
+    let graph = load_graph(args, &graph_name);
+    let load_ms = milliseconds(load_start);
+
+    let row = |source_label: &str, target_label: &str, error: Option<String>| Row {
+        graph: graph_name.clone(),
+        source_label: source_label.to_string(),
+        target_label: target_label.to_string(),
+        nodes: None,
+        distance: None,
+        path: None,
+        path_labels: String::new(),
+        load_ms,
+        query_ms: None,
+        error,
+    };
+    let graph = match graph {
+        Ok(graph) => graph,
+        Err(error) => return vec![row("", "", Some(error.to_string()))],
+    };
+
+    queries
+        .iter()
+        .map(|(source_label, target_label)| {
+            let (source, target) = match (node(&graph, source_label), node(&graph, target_label)) {
+                (Ok(source), Ok(target)) => (source, target),
+                (Err(error), _) | (_, Err(error)) => {
+                    return row(source_label, target_label, Some(error.to_string()))
+                }
+            };
+
+            let query_start = Instant::now();
+            let shortest_path = find_shortest_path(&graph, source, target, algorithm);
+            let query_ms = milliseconds(query_start);
+
+            let (distance, path) = shortest_path.unzip();
+            let path_labels = path.iter().flatten().map(|node| graph.label_of(*node));
+            Row {
+                nodes: Some((source, target)),
+                distance,
+                path_labels: path_labels.collect::<Vec<_>>().join(" "),
+                path,
+                query_ms: Some(query_ms),
+                ..row(source_label, target_label, None)
+            }
+        })
+        .collect()
+}
+
+/// Quotes a CSV field when needed (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The rows as an aligned text table, CSV and JSON.
+fn render(rows: &[Row], graphs: usize, algorithm: &str, threads: usize, total_ms: f64) -> Report {
+    let show = |value: Option<String>| value.unwrap_or_default();
+    let cells = rows
+        .iter()
+        .map(|row| {
+            [
+                row.graph.clone(),
+                row.source_label.clone(),
+                row.target_label.clone(),
+                show(row.distance.map(|distance| distance.to_string())),
+                row.path_labels.clone(),
+                format!("{:.3}", row.load_ms),
+                show(row.query_ms.map(|query_ms| format!("{:.3}", query_ms))),
+                show(row.error.clone()),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let header = [
+        "graph", "source", "target", "distance", "path", "load_ms", "query_ms", "error",
+    ];
+
+    let mut csv = header.join(",") + "\n";
+    for row_cells in &cells {
+        let fields = row_cells
+            .iter()
+            .map(|cell| csv_field(cell))
+            .collect::<Vec<_>>();
+        csv += &(fields.join(",") + "\n");
+    }
+
+    let mut widths = header.map(str::len);
+    for row_cells in &cells {
+        for (width, cell) in widths.iter_mut().zip(row_cells) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut text = String::new();
+    for row_cells in std::iter::once(&header.map(str::to_string)).chain(&cells) {
+        let line = row_cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>();
+        text += line.join("  ").trim_end();
+        text += "\n";
+    }
+    text += &format!(
+        "{} graphs, {} rows in {:.3} ms on {} threads\n",
+        graphs,
+        rows.len(),
+        total_ms,
+        threads
+    );
+
+    let json_rows = rows
+        .iter()
+        .map(|row| {
+            Json::Object(vec![
+                ("graph", row.graph.as_str().into()),
+                (
+                    "source",
+                    row.nodes.map_or(Json::Null, |(source, _)| node_id(source)),
+                ),
+                (
+                    "target",
+                    row.nodes.map_or(Json::Null, |(_, target)| node_id(target)),
+                ),
+                ("distance", row.distance.into()),
+                (
+                    "path",
+                    row.path.as_ref().map_or(Json::Null, |path| {
+                        Json::Array(path.iter().copied().map(node_id).collect())
+                    }),
+                ),
+                ("load_ms", row.load_ms.into()),
+                ("query_ms", row.query_ms.into()),
+                ("error", row.error.clone().into()),
+            ])
+        })
+        .collect();
+    let json = Json::Object(vec![
+        ("algorithm", algorithm.into()),
+        ("threads", threads.into()),
+        ("total_ms", total_ms.into()),
+        ("rows", Json::Array(json_rows)),
+    ]);
+
+    Report {
+        text,
+        json,
+        csv: Some(csv),
+    }
+}
+
+pub fn batch(args: &Args, algorithms: &'static [&'static str]) -> Result<Report, CliError> {
+    let [input, queries_path] = args.positional(["<directory|glob>", "<queries>"])?;
+    let algorithm = args.algorithm(algorithms)?;
+    let threads = match args.parsed_option::<usize>("threads")? {
+        Some(0) => return Err(CliError::Usage("--threads must be at least 1".to_string())),
+        Some(threads) => threads,
+        None => thread::available_parallelism().map_or(1, usize::from),
+    };
+    let files = graph_files(input)?;
+    let queries = read_queries(queries_path)?;
+    let threads = threads.min(files.len());
+
+    // each worker takes the next unclaimed graph until none are left
+    let start = Instant::now();
+    let next_file = AtomicUsize::new(0);
+    let mut results = thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = vec![];
+                    loop {
+                        let index = next_file.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = files.get(index) else {
+                            break results;
+                        };
+                        results.push((index, run_graph(args, path, &queries, algorithm)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    let total_ms = milliseconds(start);
+
+    results.sort_by_key(|(index, _)| *index);
+    let rows = results
+        .into_iter()
+        .flat_map(|(_, rows)| rows)
+        .collect::<Vec<_>>();
+
+    Ok(render(&rows, files.len(), algorithm, threads, total_ms))
+}
@@ -0,0 +1,715 @@
+//! The `graphtool` subcommands. Each one builds a `Report` holding both its
+//! text and its JSON rendering.
+use std::{ffi::OsStr, fs, path::Path};
+
+use ex06_492253_sanchez_torres_andres_alam::graph::{
+    generate::{self, WeightDistribution},
+    stats::GraphStats,
+    Graph, NodeNo,
+};
+
+use crate::{
+    args::{parse_value, Args},
+    batch,
+    json::Json,
+    CliError,
+};
+
+pub type Node = u32;
+pub type Weight = f64;
+
+pub struct Report {
+    pub text: String,
+    pub json: Json,
+    /// Table for `--format csv`, `None` unless `Command::csv`.
+    pub csv: Option<String>,
+}
+
+pub struct Command {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub summary: &'static str,
+    /// Extra lines for the command's `--help`.
+    pub details: &'static [&'static str],
+    /// Options besides `--format`, `--directed` and `--help`.
+    pub options: &'static [&'static str],
+    /// Whether `--format csv` is supported.
+    pub csv: bool,
+    /// Accepted `--algorithm` values, the default first.
+    pub algorithms: &'static [&'static str],
+    pub run: fn(&Args, &'static [&'static str]) -> Result<Report, CliError>,
+}
+
+#[cfg(feature = "parallel")]
+const BFS_ALGORITHMS: &[&str] = &["queue", "direction-optimizing"];
+#[cfg(not(feature = "parallel"))]
+const BFS_ALGORITHMS: &[&str] = &["queue"];
+
+#[cfg(feature = "parallel")]
+const SP_ALGORITHMS: &[&str] = &["dijkstra", "delta-stepping"];
+#[cfg(not(feature = "parallel"))]
+const SP_ALGORITHMS: &[&str] = &["dijkstra"];
+
+pub const COMMANDS: [Command; 10] = [
+    Command {
+        name: "info",
+        usage: "info <graph>",
+        summary: "Profile: size, header check, degrees, weights, components, structure",
+        details: &[],
+        options: &["no-cache"],
+        csv: false,
+        algorithms: &[],
+        run: info,
+    },
+    Command {
+        name: "bfs",
+        usage: "bfs <graph> <start>",
+        summary: "Breadth-first levels from a node",
+        details: &[],
+        options: &["no-cache", "algorithm"],
+        csv: false,
+        algorithms: BFS_ALGORITHMS,
+        run: bfs,
+    },
+    Command {
+        name: "components",
+        usage: "components <graph>",
+        summary: "Connected components and their sizes",
+        details: &[],
+        options: &["no-cache"],
+        csv: false,
+        algorithms: &[],
+        run: components,
+    },
+    Command {
+        name: "sp",
+        usage: "sp <graph> <source> <target>",
+        summary: "Shortest path between two nodes",
+        details: &[],
+        options: &["no-cache", "algorithm"],
+        csv: false,
+        algorithms: SP_ALGORITHMS,
+        run: shortest_path,
+    },
+    Command {
+        name: "mst",
+        usage: "mst <graph>",
+        summary: "Minimum spanning tree (forest)",
+        details: &[],
+        options: &["no-cache", "algorithm"],
+        csv: false,
+        algorithms: &["kruskal"],
+        run: minimum_spanning_tree,
+    },
+    Command {
+        name: "apsp",
+        usage: "apsp <graph>",
+        summary: "All pairs shortest path distances",
+        details: &[],
+        options: &["no-cache", "algorithm"],
+        csv: false,
+        algorithms: SP_ALGORITHMS,
+        run: all_pairs_shortest_paths,
+    },
+    Command {
+        name: "convert",
+        usage: "convert <input> <output>",
+        summary: "Convert a graph to another file format",
+        details: &[
+            "Input: .gph, .gphc (binary cache) or .col (DIMACS)",
+            "Output: .gph, .gphc or .dot (Graphviz)",
+        ],
+        options: &["no-cache"],
+        csv: false,
+        algorithms: &[],
+        run: convert,
+    },
+    Command {
+        name: "generate",
+        usage: "generate <model> <parameters...> --output <file>",
+        summary: "Seeded random graph, written as .gph",
+        details: &[
+            "Models:",
+            "  gnp N P             Erdős–Rényi, each edge with probability P",
+            "  gnm N M             Erdős–Rényi, M distinct edges",
+            "  ba N M              Barabási–Albert, M edges per new node",
+            "  ws N K BETA         Watts–Strogatz ring of degree K, rewired with probability BETA",
+            "  geometric N RADIUS  Points in the unit square joined within RADIUS",
+            "  grid ROWS COLS      Grid lattice",
+            "  torus ROWS COLS     Grid lattice with wrap-around",
+            "  tree N              Uniform random tree",
+            "",
+            "Weights: const:V, uniform:LOW:HIGH, int:LOW:HIGH, exp:MEAN or normal:MEAN:STD_DEV",
+        ],
+        options: &["output", "seed", "weights"],
+        csv: false,
+        algorithms: &[],
+        run: generate,
+    },
+    Command {
+        name: "batch",
+        usage: "batch <directory|glob> <queries>",
+        summary: "Shortest path queries over many graphs, in parallel",
+        details: &[
+            "Runs every `<source> <target>` line of the queries file (# starts a comment)",
+            "on every .gph file of the directory, or on the files matching a glob such",
+            "as 'graphs/b1*.gph' (* and ? in the file name). Prints one table row per",
+            "graph and query with load and query times; --format csv is supported.",
+        ],
+        options: &["no-cache", "algorithm", "threads"],
+        csv: true,
+        algorithms: SP_ALGORITHMS,
+        run: batch::batch,
+    },
+    Command {
+        name: "centrality",
+        usage: "centrality <graph>",
+        summary: "Node centrality scores, highest first",
+        details: &[],
+        options: &["no-cache", "algorithm", "weighted", "top"],
+        csv: false,
+        algorithms: &["degree", "closeness", "betweenness", "pagerank"],
+        run: centrality,
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|command| command.name == name)
+}
+
+/// Reads a graph by extension (.gphc caches, .col DIMACS, otherwise .gph
+/// through its cache unless `--no-cache`), with names from a `.names` file
+/// next to it.
+pub fn load_graph(args: &Args, path: &str) -> Result<Graph<Node, Weight>, CliError> {
+    let failed = |error| CliError::Failed(format!("{}: {}", path, error));
+    let mut graph = match Path::new(path).extension().and_then(OsStr::to_str) {
+        Some("gphc") => Graph::try_read_cache(path).map_err(failed)?,
+        Some("col") => Graph::try_read_from_dimacs(path).map_err(failed)?,
+        _ if args.flag("no-cache") => Graph::try_read_from_file(path).map_err(failed)?,
+        _ => Graph::try_read_cached(path).map_err(failed)?,
+    };
+
+    let names_path = Path::new(path).with_extension("names");
+    if names_path.exists() {
+        let names_path = names_path.to_str().unwrap();
+        graph
+            .try_read_node_names(names_path)
+            .map_err(|error| CliError::Failed(format!("{}: {}", names_path, error)))?;
+    }
+
+    Ok(graph)
+}
+
+pub fn node(graph: &Graph<Node, Weight>, label: &str) -> Result<Node, CliError> {
+    graph
+        .node_from_label(label)
+        .ok_or_else(|| CliError::Failed(format!("no node {} in the graph", label)))
+}
+
+/// 1-based id, as in .gph files.
+pub fn node_id(node: Node) -> Json {
+    Json::from(node.to_index() + 1)
+}
+
+fn stats_json(stats: &GraphStats<Weight>) -> Json {
+    let degree = stats.degree.as_ref().map(|degree| {
+        Json::Object(vec![
+            ("min", degree.min.into()),
+            ("max", degree.max.into()),
+            ("mean", degree.mean.into()),
+            ("median", degree.median.into()),
+            ("isolated", degree.isolated.into()),
+        ])
+    });
+    let weight = stats.weight.as_ref().map(|weight| {
+        Json::Object(vec![
+            ("min", weight.min.into()),
+            ("max", weight.max.into()),
+            ("mean", weight.mean.into()),
+        ])
+    });
+
+    Json::Object(vec![
+        ("vertices", stats.vertices.into()),
+        ("edges", stats.edges.into()),
+        ("declared_edges", stats.declared_edges.into()),
+        ("header_matches", stats.header_matches().into()),
+        ("self_loops", stats.self_loops.into()),
+        ("multi_edges", stats.multi_edges.into()),
+        ("degree", degree.into()),
+        ("weight", weight.into()),
+        ("components", stats.components().into()),
+        ("component_sizes", stats.component_sizes.clone().into()),
+        ("density", stats.density.into()),
+        ("is_tree", stats.is_tree.into()),
+        ("is_forest", stats.is_forest.into()),
+        ("is_bipartite", stats.is_bipartite.into()),
+    ])
+}
+
+fn info(args: &Args, _: &[&str]) -> Result<Report, CliError> {
+    let [path] = args.positional(["<graph>"])?;
+    let stats = load_graph(args, path)?.stats();
+
+    Ok(Report {
+        text: stats.to_string(),
+        json: stats_json(&stats),
+        csv: None,
+    })
+}
+
+fn bfs(args: &Args, algorithms: &'static [&'static str]) -> Result<Report, CliError> {
+    let [path, start_label] = args.positional(["<graph>", "<start>"])?;
+    let algorithm = args.algorithm(algorithms)?;
+    let graph = load_graph(args, path)?;
+    let start = node(&graph, start_label)?;
+
+    let levels: Vec<Option<usize>> = match algorithm {
+        #[cfg(feature = "parallel")]
+        "direction-optimizing" => graph.par_bfs_levels(start.to_index()),
+        _ => {
+            let tree = graph.bfs_tree(start);
+            (0..graph.vertices() as Node)
+                .map(|node| (tree.root_of(node) == start).then(|| tree.depth(node)))
+                .collect()
+        }
+    };
+
+    let depth = levels.iter().flatten().max().copied().unwrap_or(0);
+    let mut level_sizes = vec![0usize; depth + 1];
+    for level in levels.iter().flatten() {
+        level_sizes[*level] += 1;
+    }
+    let reached = level_sizes.iter().sum::<usize>();
+
+    let text = format!(
+        "start: {}\nreached: {}\ndepth: {}\nlevel sizes: {}\n",
+        graph.label_of(start),
+        reached,
+        depth,
+        level_sizes
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    let json = Json::Object(vec![
+        ("start", node_id(start)),
+        ("algorithm", algorithm.into()),
+        ("reached", reached.into()),
+        ("depth", depth.into()),
+        ("level_sizes", level_sizes.into()),
+    ]);
+
+    Ok(Report {
+        text,
+        json,
+        csv: None,
+    })
+}
+
+fn components(args: &Args, _: &[&str]) -> Result<Report, CliError> {
+    let [path] = args.positional(["<graph>"])?;
+    let graph = load_graph(args, path)?;
+
+    let sizes = graph.stats().component_sizes;
+
+    let text = format!(
+        "components: {}\nsizes: {}\n",
+        sizes.len(),
+        sizes
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    let json = Json::Object(vec![
+        ("components", sizes.len().into()),
+        ("sizes", sizes.into()),
+    ]);
+
+    Ok(Report {
+        text,
+        json,
+        csv: None,
+    })
+}
+
+/// Bucket width for delta-stepping: the mean edge cost.
+#[cfg(feature = "parallel")]
+fn delta(graph: &Graph<Node, Weight>) -> Weight {
+    let edges = graph.edges();
+    let mean = edges.iter().map(|(_, _, cost)| cost).sum::<f64>() / edges.len().max(1) as f64;
+    if mean > 0.0 {
+        mean
+    } else {
+        1.0
+    }
+}
+
+/// Shortest path with one of `SP_ALGORITHMS`.
+pub fn find_shortest_path(
+    graph: &Graph<Node, Weight>,
+    source: Node,
+    target: Node,
+    algorithm: &str,
+) -> Option<(Weight, Vec<Node>)> {
+    match algorithm {
+        #[cfg(feature = "parallel")]
+        "delta-stepping" => graph.par_shortest_path(source, target, delta(graph)),
+        _ => graph.shortest_path(source, target),
+    }
+}
+
+fn shortest_path(args: &Args, algorithms: &'static [&'static str]) -> Result<Report, CliError> {
+    let [path, source_label, target_label] =
+        args.positional(["<graph>", "<source>", "<target>"])?;
+    let algorithm = args.algorithm(algorithms)?;
+    let graph = load_graph(args, path)?;
+    let (source, target) = (node(&graph, source_label)?, node(&graph, target_label)?);
+
+    let shortest_path = find_shortest_path(&graph, source, target, algorithm);
+
+    let text = match &shortest_path {
+        Some((distance, path)) => format!(
+            "distance: {}\npath: {}\n",
+            distance,
+            path.iter()
+                .map(|node| graph.label_of(*node))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+        None => format!(
+            "no path from {} to {}\n",
+            graph.label_of(source),
+            graph.label_of(target)
+        ),
+    };
+    let (distance, path) = match shortest_path {
+        Some((distance, path)) => (
+            Json::from(distance),
+            Json::Array(path.into_iter().map(node_id).collect()),
+        ),
+        None => (Json::Null, Json::Null),
+    };
+    let json = Json::Object(vec![
+        ("source", node_id(source)),
+        ("target", node_id(target)),
+        ("algorithm", algorithm.into()),
+        ("distance", distance),
+        ("path", path),
+    ]);
+
+    Ok(Report {
+        text,
+        json,
+        csv: None,
+    })
+}
+
+fn minimum_spanning_tree(
+    args: &Args,
+    algorithms: &'static [&'static str],
+) -> Result<Report, CliError> {
+    let [path] = args.positional(["<graph>"])?;
+    args.algorithm(algorithms)?;
+    let graph = load_graph(args, path)?;
+
+    let edges = graph.minimum_spanning_tree_edges();
+    let cost = edges.iter().map(|(_, _, cost)| cost).sum::<f64>();
+    let trees = graph.vertices() - edges.len();
+
+    let mut text = format!("cost: {}\nedges: {}\ntrees: {}\n", cost, edges.len(), trees);
+    for (from, to, cost) in &edges {
+        text += &format!(
+            "{} {} {}\n",
+            graph.label_of(*from),
+            graph.label_of(*to),
+            cost
+        );
+    }
+    let json = Json::Object(vec![
+        ("cost", cost.into()),
+        ("trees", trees.into()),
+        (
+            "edges",
+            Json::Array(
+                edges
+                    .into_iter()
+                    .map(|(from, to, cost)| {
+                        Json::Array(vec![node_id(from), node_id(to), cost.into()])
+                    })
+                    .collect(),
+            ),
+        ),
+    ]);
+
+    Ok(Report {
+        text,
+        json,
+        csv: None,
+    })
+}
+
+fn all_pairs_shortest_paths(
+    args: &Args,
+    algorithms: &'static [&'static str],
+) -> Result<Report, CliError> {
+    let [path] = args.positional(["<graph>"])?;
+    let algorithm = args.algorithm(algorithms)?;
+    let graph = load_graph(args, path)?;
+
+    let nodes = 0..graph.vertices() as Node;
+    let distances = nodes
+        .clone()
+        .map(|source| {
+            let tree = match algorithm {
+                #[cfg(feature = "parallel")]
+                "delta-stepping" => graph.par_shortest_path_tree(source, delta(&graph)),
+                _ => graph.shortest_path_tree(source),
+            };
+            nodes
+                .clone()
+                .map(|target| tree.distance(target))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut text = String::new();
+    for row in &distances {
+        let row = row
+            .iter()
+            .map(|distance| distance.map_or("-".to_string(), |distance| distance.to_string()))
+            .collect::<Vec<_>>();
+        text += &row.join(" ");
+        text += "\n";
+    }
+    let json = Json::Object(vec![
+        ("vertices", graph.vertices().into()),
+        ("algorithm", algorithm.into()),
+        ("distances", distances.into()),
+    ]);
+
+    Ok(Report {
+        text,
+        json,
+        csv: None,
+    })
+}
+
+fn convert(args: &Args, _: &[&str]) -> Result<Report, CliError> {
+    let [input, output] = args.positional(["<input>", "<output>"])?;
+    let graph = load_graph(args, input)?;
+
+    let failed = |error: std::io::Error| CliError::Failed(format!("{}: {}", output, error));
+    match Path::new(output).extension().and_then(OsStr::to_str) {
+        Some("gph") => graph.write_gph(output).map_err(failed)?,
+        Some("gphc") => graph.write_cache(output).map_err(failed)?,
+        Some("dot") => fs::write(output, graph.to_dot(None)).map_err(failed)?,
+        _ => {
+            return Err(CliError::Usage(format!(
+                "unknown output format for {} (expected .gph, .gphc or .dot)",
+                output
+            )))
+        }
+    }
+
+    Ok(written(&graph, output))
+}
+
+fn written(graph: &Graph<Node, Weight>, output: &str) -> Report {
+    let edges = graph.edges().len();
+    Report {
+        text: format!(
+            "wrote {} ({} vertices, {} edges)\n",
+            output,
+            graph.vertices(),
+            edges
+        ),
+        json: Json::Object(vec![
+            ("output", output.into()),
+            ("vertices", graph.vertices().into()),
+            ("edges", edges.into()),
+        ]),
+        csv: None,
+    }
+}
+
+/// `const:V`, `uniform:LOW:HIGH`, `int:LOW:HIGH`, `exp:MEAN` or
+/// `normal:MEAN:STD_DEV`.
+fn weight_distribution(spec: &str) -> Result<WeightDistribution, CliError> {
+    let parts = spec.split(':').collect::<Vec<_>>();
+    let number = |value: &str| parse_value::<f64>("weights", value);
+    let distribution = match parts.as_slice() {
+        ["const", value] => WeightDistribution::Constant(number(value)?),
+        ["uniform", low, high] if number(low)? < number(high)? => WeightDistribution::Uniform {
+            low: number(low)?,
+            high: number(high)?,
+        },
+        ["int", low, high] => {
+            let (low, high) = (parse_value("weights", low)?, parse_value("weights", high)?);
+            if low > high {
+                return Err(CliError::Usage(format!("invalid weights: {}", spec)));
+            }
+            WeightDistribution::UniformInt { low, high }
+        }
+        ["exp", mean] if number(mean)? > 0.0 => WeightDistribution::Exponential {
+            mean: number(mean)?,
+        },
+        ["normal", mean, std_dev] if number(std_dev)? >= 0.0 => WeightDistribution::Normal {
+            mean: number(mean)?,
+            std_dev: number(std_dev)?,
+        },
+        _ => {
+            return Err(CliError::Usage(format!(
+                "invalid weights: {} (expected const:V, uniform:LOW:HIGH, int:LOW:HIGH, exp:MEAN or normal:MEAN:STD_DEV)",
+                spec
+            )))
+        }
+    };
+
+    Ok(distribution)
+}
+
+fn generate(args: &Args, _: &[&str]) -> Result<Report, CliError> {
+    let output = args
+        .option("output")
+        .ok_or_else(|| CliError::Usage("generate needs --output <file>".to_string()))?;
+    let seed = args.parsed_option::<u64>("seed")?.unwrap_or(1);
+    let weights = weight_distribution(args.option("weights").unwrap_or("const:1"))?;
+
+    let (model, parameters) = args
+        .positional_all()
+        .split_first()
+        .ok_or_else(|| CliError::Usage("generate needs a model".to_string()))?;
+    let parameter_names: &[&str] = match model.as_str() {
+        "gnp" => &["N", "P"],
+        "gnm" | "ba" => &["N", "M"],
+        "ws" => &["N", "K", "BETA"],
+        "geometric" => &["N", "RADIUS"],
+        "grid" | "torus" => &["ROWS", "COLS"],
+        "tree" => &["N"],
+        _ => return Err(CliError::Usage(format!("unknown model {}", model))),
+    };
+    if parameters.len() != parameter_names.len() {
+        return Err(CliError::Usage(format!(
+            "{} expects {}",
+            model,
+            parameter_names.join(" ")
+        )));
+    }
+    let count = |index: usize| parse_value::<usize>(parameter_names[index], &parameters[index]);
+    let real = |index: usize| parse_value::<f64>(parameter_names[index], &parameters[index]);
+    let invalid = |message: &str| Err(CliError::Usage(format!("{}: {}", model, message)));
+
+    let vertices = match model.as_str() {
+        "grid" | "torus" => count(0)?.checked_mul(count(1)?),
+        _ => Some(count(0)?),
+    };
+    if vertices.is_none_or(|vertices| vertices > Node::MAX as usize) {
+        return invalid("too many nodes");
+    }
+
+    let graph: Graph<Node, Weight> = match model.as_str() {
+        "gnp" => {
+            let p = real(1)?;
+            if !(0.0..=1.0).contains(&p) {
+                return invalid("P must be in [0, 1]");
+            }
+            generate::erdos_renyi_gnp(count(0)?, p, &weights, seed)
+        }
+        "gnm" => {
+            let (n, m) = (count(0)?, count(1)?);
+            if m > n * n.saturating_sub(1) / 2 {
+                return invalid("M is more than N(N-1)/2");
+            }
+            generate::erdos_renyi_gnm(n, m, &weights, seed)
+        }
+        "ba" => {
+            let (n, m) = (count(0)?, count(1)?);
+            if m < 1 || m >= n {
+                return invalid("needs 1 <= M < N");
+            }
+            generate::barabasi_albert(n, m, &weights, seed)
+        }
+        "ws" => {
+            let (n, k, beta) = (count(0)?, count(1)?, real(2)?);
+            if !k.is_multiple_of(2) || k >= n || !(0.0..=1.0).contains(&beta) {
+                return invalid("needs an even K < N and BETA in [0, 1]");
+            }
+            generate::watts_strogatz(n, k, beta, &weights, seed)
+        }
+        "geometric" => {
+            let radius = real(1)?;
+            if radius.is_nan() || radius < 0.0 {
+                return invalid("RADIUS must not be negative");
+            }
+            generate::random_geometric(count(0)?, radius, &weights, seed)
+        }
+        "grid" => generate::grid(count(0)?, count(1)?, &weights, seed),
+        "torus" => generate::torus(count(0)?, count(1)?, &weights, seed),
+        _ => generate::random_tree(count(0)?, &weights, seed),
+    };
+
+    graph
+        .write_gph(output)
+        .map_err(|error| CliError::Failed(format!("{}: {}", output, error)))?;
+
+    Ok(written(&graph, output))
+}
+
+fn centrality(args: &Args, algorithms: &'static [&'static str]) -> Result<Report, CliError> {
+    let [path] = args.positional(["<graph>"])?;
+    let algorithm = args.algorithm(algorithms)?;
+    let weighted = args.flag("weighted");
+    if weighted && !["closeness", "betweenness"].contains(&algorithm) {
+        return Err(CliError::Usage(
+            "--weighted only applies to closeness and betweenness".to_string(),
+        ));
+    }
+    let top = args.parsed_option::<usize>("top")?;
+    let graph = load_graph(args, path)?;
+
+    let scores = match algorithm {
+        "closeness" => graph.closeness_centrality(weighted),
+        "betweenness" => graph.betweenness_centrality(weighted),
+        "pagerank" => graph.pagerank(0.85, 1e-10),
+        _ => graph.degree_centrality(),
+    };
+    let mut ranking = (0..graph.vertices() as Node).collect::<Vec<_>>();
+    ranking.sort_by(|first, second| {
+        scores[second.to_index()]
+            .total_cmp(&scores[first.to_index()])
+            .then(first.cmp(second))
+    });
+    ranking.truncate(top.unwrap_or(ranking.len()));
+
+    let mut text = String::new();
+    for node in &ranking {
+        text += &format!("{} {}\n", graph.label_of(*node), scores[node.to_index()]);
+    }
+    let json = Json::Object(vec![
+        ("algorithm", algorithm.into()),
+        ("weighted", weighted.into()),
+        (
+            "scores",
+            Json::Array(
+                ranking
+                    .into_iter()
+                    .map(|node| {
+                        Json::Object(vec![
+                            ("node", node_id(node)),
+                            ("score", scores[node.to_index()].into()),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+    ]);
+
+    Ok(Report {
+        text,
+        json,
+        csv: None,
+    })
+}
@@ -0,0 +1,162 @@
+//! `graphtool`: one command line front end for the graph library, e.g.
+//!
+//! ```text
+//! graphtool sp graphs/b01.gph 2 45 --format json
+//! graphtool generate ba 10000 3 --weights int:1:100 -o ba.gph
+//! ```
+//!
+//! Exits with 0 on success, 1 when the command fails (e.g. an unreadable
+//! graph) and 2 on invalid usage.
+use std::{env, fmt, process::ExitCode};
+
+use args::{Args, Format};
+use commands::{Command, COMMANDS};
+
+mod args;
+mod batch;
+mod commands;
+mod json;
+
+pub enum CliError {
+    /// Bad command line, reported with a pointer to `--help`.
+    Usage(String),
+    Failed(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(message) | CliError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+const OPTIONS: [(&str, &str); 11] = [
+    (
+        "--format <text|json|csv>",
+        "Output format (default text, csv for batch)",
+    ),
+    (
+        "--algorithm <name>",
+        "Algorithm to run, see the command's help",
+    ),
+    ("--directed", "Treat edges as directed (not supported yet)"),
+    (
+        "--no-cache",
+        "Parse .gph text instead of using its binary cache",
+    ),
+    ("--weighted", "Use edge costs as distances (centrality)"),
+    ("--top <n>", "Only show the n highest scores (centrality)"),
+    ("-o, --output <file>", "File to write (generate)"),
+    ("--seed <n>", "Random seed (generate, default 1)"),
+    (
+        "--threads <n>",
+        "Worker threads (batch, default: available cores)",
+    ),
+    (
+        "--weights <spec>",
+        "Edge cost distribution (generate, default const:1)",
+    ),
+    (
+        "-h, --help",
+        "Show help, for a command with `<command> --help`",
+    ),
+];
+
+fn print_help() {
+    println!("Usage: graphtool <command> [arguments] [options]\n");
+    println!("Nodes are given by name (from a .names file next to the graph) or 1-based id.\n");
+    println!("Commands:");
+    for command in &COMMANDS {
+        println!("  {:<12}{}", command.name, command.summary);
+    }
+    println!("\nOptions:");
+    for (option, description) in OPTIONS {
+        println!("  {:<24}{}", option, description);
+    }
+}
+
+fn print_command_help(command: &Command) {
+    println!("Usage: graphtool {} [options]\n", command.usage);
+    println!("{}\n", command.summary);
+    for line in command.details {
+        println!("{}", line);
+    }
+    if !command.details.is_empty() {
+        println!();
+    }
+    if !command.algorithms.is_empty() {
+        println!(
+            "Algorithms (--algorithm): {}\n",
+            command.algorithms.join(", ")
+        );
+    }
+    println!("Options:");
+    for (option, description) in OPTIONS {
+        let name = option.rsplit("--").next().unwrap();
+        let name = name.split(' ').next().unwrap();
+        if ["format", "directed", "help"].contains(&name) || command.options.contains(&name) {
+            println!("  {:<24}{}", option, description);
+        }
+    }
+}
+
+fn run() -> Result<(), CliError> {
+    let args = Args::parse(env::args().skip(1))?;
+
+    let command = match args.command.as_deref() {
+        None if args.flag("help") => {
+            print_help();
+            return Ok(());
+        }
+        None => return Err(CliError::Usage("missing command".to_string())),
+        Some("help") => {
+            print_help();
+            return Ok(());
+        }
+        Some(name) => commands::find(name)
+            .ok_or_else(|| CliError::Usage(format!("unknown command {}", name)))?,
+    };
+    if args.flag("help") {
+        print_command_help(command);
+        return Ok(());
+    }
+
+    let format = args.format()?;
+    if format == Format::Csv && !command.csv {
+        return Err(CliError::Usage(format!(
+            "{} has no csv output, use text or json",
+            command.name
+        )));
+    }
+    args.check_options(command.name, command.options)?;
+    if args.flag("directed") {
+        return Err(CliError::Failed(
+            "directed graphs are not supported, every .gph edge is undirected".to_string(),
+        ));
+    }
+
+    let report = (command.run)(&args, command.algorithms)?;
+    match format {
+        Format::Text => print!("{}", report.text),
+        Format::Json => println!("{}", report.json),
+        Format::Csv => print!("{}", report.csv.unwrap_or_default()),
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error @ CliError::Usage(_)) => {
+            eprintln!("graphtool: {}", error);
+            eprintln!("Run `graphtool --help` for usage.");
+            ExitCode::from(2)
+        }
+        Err(error @ CliError::Failed(_)) => {
+            eprintln!("graphtool: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
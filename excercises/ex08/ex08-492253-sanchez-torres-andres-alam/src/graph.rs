@@ -1,13 +1,49 @@
 use std::{
     cmp::{self, Ordering},
     collections::{BinaryHeap, VecDeque},
-    fmt,
-    fs::File,
-    io::{BufRead, BufReader},
-    ops,
+    fmt, ops,
+    path::Path,
     str::FromStr,
 };
 
+pub mod attributes;
+pub mod cache;
+pub mod centrality;
+pub mod coloring;
+pub mod community;
+pub mod constrained;
+pub mod cycles;
+pub mod dynamic;
+mod error;
+pub mod euler;
+pub mod export;
+pub mod generate;
+mod gph;
+pub mod hierarchy;
+pub mod isomorphism;
+mod labels;
+mod matching;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod rng;
+pub mod sensitivity;
+pub mod spectral;
+pub mod stats;
+pub mod steiner;
+#[cfg(test)]
+mod testing;
+pub mod traversal;
+pub mod tree;
+pub mod tsp;
+
+pub use attributes::{AttributedGraph, EdgeAttributes};
+use cache::FileBytes;
+pub use error::GraphError;
+pub use export::Highlight;
+use gph::GphLines;
+use labels::NodeNames;
+pub use tsp::{MetricClosure, Tour};
+
 pub trait NodeNo:
     ops::Add<Output = Self>
     + ops::Sub<Output = Self>
@@ -25,6 +61,7 @@ pub trait NodeNo:
     fn zero() -> Self;
     fn one() -> Self;
     fn to_index(&self) -> usize;
+    fn from_index(index: usize) -> Self;
 }
 
 pub trait Numeric:
@@ -45,10 +82,28 @@ pub trait Numeric:
     fn max_value() -> Self;
     fn zero() -> Self;
     fn one() -> Self;
+    /// Converts with `as` semantics, i.e. integers truncate and saturate.
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(&self) -> f64;
+    /// Clamps to `MIN` and to just below `max_value()` instead of
+    /// overflowing, so a saturated path cost never wraps around and never
+    /// reads as the `max_value()` "unreached" sentinel.
+    fn saturating_add(self, other: Self) -> Self;
+    fn is_nan(&self) -> bool;
+    /// Total order that also ranks NaN (after every other float), used by
+    /// heaps and sorts instead of `partial_cmp`.
+    fn total_cmp(&self, other: &Self) -> Ordering;
+    /// Primitive type name, e.g. `"u32"`, tagging binary caches.
+    fn type_name() -> &'static str;
+    /// Size of the little-endian encoding.
+    fn byte_size() -> usize;
+    fn write_le_bytes(&self, bytes: &mut Vec<u8>);
+    /// Decodes the first `byte_size()` bytes.
+    fn read_le_bytes(bytes: &[u8]) -> Self;
 }
 
 macro_rules! impl_numeric {
-    ($dtype: ident) => {
+    ($dtype: ident, $kind: ident) => {
         impl Numeric for $dtype {
             fn max_value() -> Self {
                 $dtype::MAX
@@ -59,10 +114,76 @@ macro_rules! impl_numeric {
             fn one() -> Self {
                 1 as $dtype
             }
+            fn from_f64(value: f64) -> Self {
+                value as $dtype
+            }
+            fn to_f64(&self) -> f64 {
+                *self as f64
+            }
+            fn type_name() -> &'static str {
+                stringify!($dtype)
+            }
+            fn byte_size() -> usize {
+                std::mem::size_of::<$dtype>()
+            }
+            fn write_le_bytes(&self, bytes: &mut Vec<u8>) {
+                bytes.extend_from_slice(&self.to_le_bytes());
+            }
+            fn read_le_bytes(bytes: &[u8]) -> Self {
+                $dtype::from_le_bytes(bytes[..std::mem::size_of::<$dtype>()].try_into().unwrap())
+            }
+            impl_numeric!(@$kind $dtype);
+        }
+    };
+    (@int $dtype: ident) => {
+        fn saturating_add(self, other: Self) -> Self {
+            $dtype::saturating_add(self, other).min($dtype::MAX - 1)
+        }
+        fn is_nan(&self) -> bool {
+            false
+        }
+        fn total_cmp(&self, other: &Self) -> Ordering {
+            self.cmp(other)
+        }
+    };
+    (@float $dtype: ident) => {
+        fn saturating_add(self, other: Self) -> Self {
+            (self + other).clamp($dtype::MIN, $dtype::MAX.next_down())
+        }
+        fn is_nan(&self) -> bool {
+            $dtype::is_nan(*self)
+        }
+        fn total_cmp(&self, other: &Self) -> Ordering {
+            $dtype::total_cmp(self, other)
         }
     };
 }
 
+/// Wraps a weight so it can be used where `Ord` is required (heaps, sort
+/// keys, maps), ordering by `Numeric::total_cmp`.
+#[derive(Clone, Copy, Debug)]
+pub struct TotalOrd<W: Numeric>(pub W);
+
+impl<W: Numeric> PartialEq for TotalOrd<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<W: Numeric> Eq for TotalOrd<W> {}
+
+impl<W: Numeric> PartialOrd for TotalOrd<W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<W: Numeric> Ord for TotalOrd<W> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
 macro_rules! impl_nodeno {
     ($dtype: ident) => {
         impl NodeNo for $dtype {
@@ -78,6 +199,9 @@ macro_rules! impl_nodeno {
             fn to_index(&self) -> usize {
                 usize::try_from(*self).unwrap()
             }
+            fn from_index(index: usize) -> Self {
+                $dtype::try_from(index).unwrap()
+            }
         }
     };
 }
@@ -87,18 +211,18 @@ impl_nodeno!(u16);
 impl_nodeno!(u32);
 impl_nodeno!(u64);
 impl_nodeno!(usize);
-impl_numeric!(u8);
-impl_numeric!(u16);
-impl_numeric!(u32);
-impl_numeric!(u64);
-impl_numeric!(u128);
-impl_numeric!(i8);
-impl_numeric!(i16);
-impl_numeric!(i32);
-impl_numeric!(i64);
-impl_numeric!(i128);
-impl_numeric!(f32);
-impl_numeric!(f64);
+impl_numeric!(u8, int);
+impl_numeric!(u16, int);
+impl_numeric!(u32, int);
+impl_numeric!(u64, int);
+impl_numeric!(u128, int);
+impl_numeric!(i8, int);
+impl_numeric!(i16, int);
+impl_numeric!(i32, int);
+impl_numeric!(i64, int);
+impl_numeric!(i128, int);
+impl_numeric!(f32, float);
+impl_numeric!(f64, float);
 
 #[derive(Clone)]
 struct Edge<T: NodeNo, W: Numeric> {
@@ -107,10 +231,11 @@ struct Edge<T: NodeNo, W: Numeric> {
     cost: W,
 }
 
-#[derive(Clone, Copy, PartialEq, PartialOrd)]
-struct SearchState<T: NodeNo, W: Numeric> {
+/// Queue entry of a search, popped by lowest `key` first.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct SearchState<T: NodeNo, K: Ord> {
     node: T,
-    cost: W,
+    key: K,
 }
 
 // impl<W: Numeric> Ord for SearchState<W> {
@@ -126,20 +251,21 @@ struct SearchState<T: NodeNo, W: Numeric> {
 //     }
 // }
 
-impl<T: NodeNo, W: Numeric> Eq for SearchState<T, W> {}
-
 // impl<W: Numeric> PartialOrd for SearchState<W> {
 //     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
 //         Some(self.cost.partial_cmp(&other.cost).unwrap_or(Ordering::Equal))
 //     }
 // }
 
-impl<T: NodeNo, W: Numeric> Ord for SearchState<T, W> {
+impl<T: NodeNo, K: Ord> Ord for SearchState<T, K> {
     fn cmp(&self, other: &Self) -> Ordering {
-        other
-            .cost
-            .partial_cmp(&self.cost)
-            .unwrap_or(Ordering::Equal)
+        other.key.cmp(&self.key)
+    }
+}
+
+impl<T: NodeNo, K: Ord> PartialOrd for SearchState<T, K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
@@ -157,90 +283,317 @@ impl<T: NodeNo, W: Numeric> Ord for SearchState<T, W> {
 struct PathDistance<T: NodeNo, W: Numeric> {
     source: T,
     cost: W,
+    // edges at the end of the path that didn't add to `cost`
+    zero_hops: usize,
+}
+
+impl<T: NodeNo, W: Numeric> PathDistance<T, W> {
+    fn unreached(source: T) -> Self {
+        PathDistance {
+            source,
+            cost: W::max_value(),
+            zero_hops: 0,
+        }
+    }
+
+    fn start(source: T) -> Self {
+        PathDistance {
+            source,
+            cost: W::zero(),
+            zero_hops: 0,
+        }
+    }
+
+    /// Orders equally cheap paths by the zero-cost edges at their end (zero
+    /// costs, or too small to change a float total), so every edge makes a
+    /// path strictly longer and ties have a fixed winner.
+    fn key(&self) -> (TotalOrd<W>, usize) {
+        (TotalOrd(self.cost), self.zero_hops)
+    }
+
+    /// This path continued from `node` over an edge of `edge_cost`.
+    fn then(&self, node: T, edge_cost: W) -> Self {
+        let cost = self.cost.saturating_add(edge_cost);
+        let zero_hops = if cost == self.cost {
+            self.zero_hops + 1
+        } else {
+            0
+        };
+        PathDistance {
+            source: node,
+            cost,
+            zero_hops,
+        }
+    }
+}
+
+/// Lowers `distance[to]` to the path to `from` continued over an edge, and
+/// between equally short paths keeps the parent `dijkstra_parent` picks.
+/// True when the distance went down, so `to` has to be (re)visited.
+fn relax<T: NodeNo, W: Numeric>(
+    distance: &mut [PathDistance<T, W>],
+    from: T,
+    to: T,
+    edge_cost: W,
+) -> bool {
+    let next = distance[from.to_index()].then(from, edge_cost);
+    let current = distance[to.to_index()];
+    match next.key().cmp(&current.key()) {
+        Ordering::Less => {
+            distance[to.to_index()] = next;
+            true
+        }
+        Ordering::Equal => {
+            let parent_key = |parent: T| (distance[parent.to_index()].key(), parent.to_index());
+            if parent_key(from) < parent_key(current.source) {
+                distance[to.to_index()].source = from;
+            }
+            false
+        }
+        Ordering::Greater => false,
+    }
+}
+
+/// The parent Dijkstra keeps for `node`, from the final distances: of the
+/// neighbours whose path continued over the edge gives `node`'s distance,
+/// the lowest by `PathDistance::key` and then by index. Lets other searches
+/// return the same paths. `None` for sources and unreachable nodes.
+fn dijkstra_parent<T: NodeNo, W: Numeric>(
+    node: T,
+    neighbours: impl IntoIterator<Item = (T, W)>,
+    mut distance: impl FnMut(T) -> Option<PathDistance<T, W>>,
+) -> Option<T> {
+    let reached = distance(node)?;
+
+    let mut parent: Option<((TotalOrd<W>, usize), T)> = None;
+    for (adj_node, edge_cost) in neighbours {
+        let Some(adj_distance) = distance(adj_node) else {
+            continue;
+        };
+        let key = adj_distance.key();
+        if adj_distance.then(adj_node, edge_cost).key() == reached.key()
+            && parent.is_none_or(|(parent_key, parent)| {
+                (key, adj_node.to_index()) < (parent_key, parent.to_index())
+            })
+        {
+            parent = Some((key, adj_node));
+        }
+    }
+
+    parent.map(|(_, parent)| parent)
+}
+
+/// Shortest path distances and parents from one source, see
+/// `Graph::shortest_path_tree`, or from the nearest of several, see
+/// `Graph::shortest_path_tree_filtered`.
+pub struct ShortestPathTree<T: NodeNo, W: Numeric> {
+    sources: Vec<T>,
+    // sources are their own parent
+    distance: Vec<PathDistance<T, W>>,
+}
+
+impl<T: NodeNo, W: Numeric> ShortestPathTree<T, W> {
+    /// The first (usually only) source.
+    pub fn source(&self) -> T {
+        self.sources[0]
+    }
+
+    pub fn sources(&self) -> &[T] {
+        &self.sources
+    }
+
+    /// `None` when `node` is not reachable from the source.
+    pub fn distance(&self, node: T) -> Option<W> {
+        let cost = self.distance[node.to_index()].cost;
+        (cost != W::max_value()).then_some(cost)
+    }
+
+    /// Previous node on the shortest path from the source, `None` for the
+    /// sources themselves and for unreachable nodes.
+    pub fn parent(&self, node: T) -> Option<T> {
+        let parent = self.distance[node.to_index()].source;
+        if parent == node || self.distance(node).is_none() {
+            return None;
+        }
+        Some(parent)
+    }
+
+    pub fn path_to(&self, target: T) -> Option<Vec<T>> {
+        self.distance(target)?;
+
+        let mut path = vec![target];
+        let mut current_node = target;
+        while let Some(parent) = self.parent(current_node) {
+            path.push(parent);
+            current_node = parent;
+        }
+        path.reverse();
+
+        Some(path)
+    }
+}
+
+/// Vertex and edge counts of a `<vertices> <edges>` header line.
+fn parse_header(line: &str, line_no: usize) -> Result<(usize, usize), GraphError> {
+    let mut values = line.split_ascii_whitespace().map(str::parse::<usize>);
+    match (values.next(), values.next(), values.next()) {
+        (Some(Ok(vertex_count)), Some(Ok(edge_count)), None) => Ok((vertex_count, edge_count)),
+        _ => Err(GraphError::InvalidHeader { line: line_no }),
+    }
+}
+
+/// Parses a 1-based node id of an edge line into a 0-based node.
+fn parse_node<T: NodeNo>(
+    raw_node: Option<&str>,
+    line_no: usize,
+    vertices: usize,
+) -> Result<T, GraphError> {
+    let raw_node = raw_node.ok_or(GraphError::InvalidEdge { line: line_no })?;
+    let node = raw_node
+        .parse::<T>()
+        .map_err(|_| GraphError::InvalidEdge { line: line_no })?;
+
+    if node == T::zero() || node.to_index() > vertices {
+        return Err(GraphError::NodeOutOfRange {
+            line: line_no,
+            node: raw_node.to_string(),
+        });
+    }
+
+    Ok(node - T::one())
 }
 
 pub struct Graph<T: NodeNo, W: Numeric> {
     adjacencies: Vec<Vec<Edge<T, W>>>,
     vertices: usize,
+    names: NodeNames,
+    // edge count of the file header, which may not match the edge lines
+    declared_edges: Option<usize>,
 }
 
 impl<T: NodeNo, W: Numeric> Graph<T, W> {
-    fn get_values_from_line(line: String) -> Vec<usize> {
-        line.trim()
-            .split(' ')
-            .map(|s| s.parse::<usize>().ok().unwrap())
-            // .expect("Can't parse values from graph"))
-            .collect::<Vec<_>>()
+    pub fn new(vertices: usize) -> Self {
+        Graph {
+            adjacencies: vec![vec![]; vertices],
+            vertices,
+            names: NodeNames::default(),
+            declared_edges: None,
+        }
     }
 
-    fn get_graph_entry(line: String) -> (T, T, W) {
-        let mut values = line.trim().split(' ');
+    pub fn vertices(&self) -> usize {
+        self.vertices
+    }
 
-        let node_0 = values
-            .next()
-            .expect("Cant read source node from edge line")
-            .parse::<T>()
-            .ok()
-            .unwrap();
-        // .expect("Cant parse source node from edge line");
-        let node_1 = values
-            .next()
-            .expect("Cant read target node from edge line")
-            .parse::<T>()
-            .ok()
-            .unwrap();
-        // .expect("Cant parse source node from edge line");
+    pub fn add_edge(&mut self, from: T, to: T, cost: W) {
+        self.adjacencies[from.to_index()].push(Edge { from, to, cost });
+        self.adjacencies[to.to_index()].push(Edge {
+            from: to,
+            to: from,
+            cost,
+        });
+    }
+
+    /// Lists every undirected edge once as `(from, to, cost)` with `from <= to`.
+    ///
+    /// Self-loops are stored twice in their adjacency list, so only every other
+    /// copy is reported.
+    pub fn edges(&self) -> Vec<(T, T, W)> {
+        let mut edges = vec![];
+
+        for adj_edges in &self.adjacencies {
+            let mut loop_copies = 0;
+            for edge in adj_edges {
+                if edge.from == edge.to {
+                    if loop_copies % 2 == 0 {
+                        edges.push((edge.from, edge.to, edge.cost));
+                    }
+                    loop_copies += 1;
+                } else if edge.from < edge.to {
+                    edges.push((edge.from, edge.to, edge.cost));
+                }
+            }
+        }
+
+        edges
+    }
+
+    fn cheapest_edge(&self, from: usize, to: usize) -> Option<W> {
+        self.adjacencies[from]
+            .iter()
+            .filter(|edge| edge.to.to_index() == to)
+            .map(|edge| edge.cost)
+            .min_by_key(|cost| TotalOrd(*cost))
+    }
+
+    /// Neighbour lists without self-loops or parallel edges, sorted by node.
+    fn simple_adjacency(&self) -> Vec<Vec<usize>> {
+        self.adjacencies
+            .iter()
+            .enumerate()
+            .map(|(node, adj_edges)| {
+                let mut neighbours = adj_edges
+                    .iter()
+                    .map(|edge| edge.to.to_index())
+                    .filter(|adj_node| *adj_node != node)
+                    .collect::<Vec<_>>();
+                neighbours.sort_unstable();
+                neighbours.dedup();
+                neighbours
+            })
+            .collect()
+    }
+
+    fn get_graph_entry(&self, line: &str, line_no: usize) -> Result<(T, T, W), GraphError> {
+        let mut values = line.split_ascii_whitespace();
+        let mut next_node = || parse_node(values.next(), line_no, self.vertices);
+
+        let node_0 = next_node()?;
+        let node_1 = next_node()?;
         let weight = values
             .next()
-            .expect("Cant read weight from edge line")
+            .ok_or(GraphError::InvalidEdge { line: line_no })?
             .parse::<W>()
-            .ok()
-            .unwrap();
+            .map_err(|_| GraphError::InvalidEdge { line: line_no })?;
+
+        if weight.is_nan() {
+            return Err(GraphError::NanWeight { line: line_no });
+        }
 
-        (node_0 - T::one(), node_1 - T::one(), weight)
+        Ok((node_0, node_1, weight))
     }
 
     pub fn read_from_file(file_path: &str) -> Self {
-        let graph_file =
-            File::open(file_path).unwrap_or_else(|_| panic!("File {} does not exist", file_path));
-        let mut reader: BufReader<File> = BufReader::new(graph_file);
-
-        let mut graph_shape_line = String::new();
-        reader
-            .read_line(&mut graph_shape_line)
-            .expect("Can't read graph shape");
-
-        let graph_shape = Self::get_values_from_line(graph_shape_line);
-        let (vertex_count, _) = match graph_shape.as_slice() {
-            [vertex_count, edge_count] => (*vertex_count, *edge_count),
-            _ => panic!("Can't parse graph size"),
-        };
+        Self::try_read_from_file(file_path)
+            .unwrap_or_else(|error| panic!("Can't load graph {}: {}", file_path, error))
+    }
 
-        let mut adjacencies = vec![vec![]; vertex_count];
-
-        for line in reader.lines() {
-            let (node_0, node_1, weight) = Self::get_graph_entry(line.unwrap());
-
-            adjacencies[node_0.to_index()].push(Edge {
-                from: node_0,
-                to: node_1,
-                cost: weight,
-            });
-            adjacencies[node_1.to_index()].push(Edge {
-                from: node_1,
-                to: node_0,
-                cost: weight,
-            });
+    /// Like `read_from_file`, but reports malformed files (including NaN
+    /// weights) as a `GraphError` instead of panicking.
+    ///
+    /// The file is scanned once as bytes (see `gph::GphLines`), so values may
+    /// be separated by any whitespace and `#`/`c` comments are skipped.
+    pub fn try_read_from_file(file_path: &str) -> Result<Self, GraphError> {
+        let bytes = FileBytes::open(Path::new(file_path))?;
+        let mut lines = GphLines::new(&bytes);
+
+        let (header_line_no, graph_shape_line) = lines.header()?;
+        let (vertex_count, edge_count) = parse_header(graph_shape_line, header_line_no)?;
+
+        let mut graph = Graph::new(vertex_count);
+        graph.declared_edges = Some(edge_count);
+
+        for line in lines {
+            let (line_no, line) = line?;
+            let (node_0, node_1, weight) = graph.get_graph_entry(line, line_no)?;
+            graph.add_edge(node_0, node_1, weight);
         }
 
-        Graph {
-            adjacencies,
-            vertices: vertex_count,
-        }
+        Ok(graph)
     }
 
     pub fn bfs_depth(&self, start: usize) -> usize {
-        let mut visited: Vec<bool> = vec![false; self.vertices.into()];
+        let mut visited: Vec<bool> = vec![false; self.vertices];
         self.bfs_depth_managed(start, &mut visited)
     }
 
@@ -270,11 +623,35 @@ impl<T: NodeNo, W: Numeric> Graph<T, W> {
         tree_depth
     }
 
+    /// BFS depth of every node reached from `start`, stopping after
+    /// `max_depth` levels if given; `None` for the other nodes.
+    fn bfs_levels(&self, start: usize, max_depth: Option<usize>) -> Vec<Option<usize>> {
+        let mut levels: Vec<Option<usize>> = vec![None; self.vertices];
+        // track (node, depth)
+        let mut bfs_queue = VecDeque::from([(start, 0)]);
+        levels[start] = Some(0);
+
+        while let Some((current_node, current_depth)) = bfs_queue.pop_front() {
+            if max_depth.is_some_and(|max_depth| current_depth >= max_depth) {
+                continue;
+            }
+            for edge in &self.adjacencies[current_node] {
+                let adj_node = edge.to.to_index();
+                if levels[adj_node].is_none() {
+                    levels[adj_node] = Some(current_depth + 1);
+                    bfs_queue.push_back((adj_node, current_depth + 1));
+                }
+            }
+        }
+
+        levels
+    }
+
     pub fn connected_components(&self) -> usize {
-        let mut visited: Vec<bool> = vec![false; self.vertices.into()];
+        let mut visited: Vec<bool> = vec![false; self.vertices];
         let mut connected_components = 0;
 
-        for node in 0..self.vertices.into() {
+        for node in 0..self.vertices {
             let is_visited = visited[node];
             if !is_visited {
                 self.bfs_depth_managed(node, &mut visited);
@@ -301,27 +678,23 @@ impl<T: NodeNo, W: Numeric> Graph<T, W> {
     }
 
     pub fn shortest_path(&self, source: T, target: T) -> Option<(W, Vec<T>)> {
-        let mut nodes_q: BinaryHeap<SearchState<T, W>> = BinaryHeap::new();
-        let mut distance = vec![
-            PathDistance {
-                source,
-                cost: W::max_value()
-            };
-            self.vertices.into()
-        ];
-
-        distance[source.to_index()] = PathDistance {
-            source,
-            cost: W::zero(),
-        };
+        let mut nodes_q: BinaryHeap<SearchState<T, TotalOrd<W>>> = BinaryHeap::new();
+        let mut distance = vec![PathDistance::unreached(source); self.vertices];
+
+        distance[source.to_index()] = PathDistance::start(source);
         nodes_q.push(SearchState {
             node: source,
-            cost: W::zero(),
+            key: TotalOrd(W::zero()),
         });
 
-        while let Some(SearchState { node, cost }) = nodes_q.pop() {
-            if node == target {
-                return Some((cost, self.rebuild_path(source, target, distance)));
+        while let Some(SearchState {
+            node,
+            key: TotalOrd(cost),
+        }) = nodes_q.pop()
+        {
+            // the target's parent is only certain once nothing as cheap is left
+            if cost > distance[target.to_index()].cost {
+                break;
             }
 
             if cost > distance[node.to_index()].cost {
@@ -330,51 +703,99 @@ impl<T: NodeNo, W: Numeric> Graph<T, W> {
 
             let adj_edges = &self.adjacencies[node.to_index()];
             for edge in adj_edges {
-                let new_cost = cost + edge.cost;
-
-                if new_cost < distance[edge.to.to_index()].cost {
+                if relax(&mut distance, node, edge.to, edge.cost) {
                     nodes_q.push(SearchState {
                         node: edge.to,
-                        cost: new_cost,
+                        key: TotalOrd(distance[edge.to.to_index()].cost),
                     });
-                    distance[edge.to.to_index()] = PathDistance {
-                        source: node,
-                        cost: new_cost,
-                    };
                 }
             }
         }
 
-        None
+        let cost = distance[target.to_index()].cost;
+        (cost != W::max_value()).then(|| (cost, self.rebuild_path(source, target, distance)))
+    }
+
+    /// Runs Dijkstra from `source` over the whole graph.
+    pub fn shortest_path_tree(&self, source: T) -> ShortestPathTree<T, W> {
+        self.filtered_dijkstra(&[source], None, None, |_| true, |_, _, _| true)
     }
 
     pub fn minimum_spanning_tree(&self) -> W {
-        let mut parent: Vec<usize> = (0..self.vertices).collect();
-        let mut edges = self.adjacencies.iter().flatten().collect::<Vec<_>>();
+        let mut cost_sum: W = W::zero();
 
-        edges.sort_by(|edge_a, edge_b| edge_a.cost.partial_cmp(&edge_b.cost).unwrap());
+        for (_, _, cost) in self.minimum_spanning_tree_edges() {
+            cost_sum += cost;
+        }
 
-        let mut cost_sum: W = W::zero();
+        cost_sum
+    }
 
-        for Edge {
-            from,
-            to,
-            cost: next_cost,
-        } in edges
-        {
-            if parent[(*from).to_index()] != parent[(*to).to_index()] {
-                cost_sum += *next_cost;
-
-                let new_parent = parent[(*from).to_index()];
-                let replaced_parent = parent[(*to).to_index()];
-                for node_parent in parent.iter_mut() {
-                    if *node_parent == replaced_parent {
-                        *node_parent = new_parent;
-                    }
-                }
+    pub fn minimum_spanning_tree_edges(&self) -> Vec<(T, T, W)> {
+        let edges = self.edges();
+        self.minimum_spanning_tree_ids(&edges)
+            .into_iter()
+            .map(|id| edges[id])
+            .collect()
+    }
+
+    /// Kruskal over `edges` as listed by `edges()`: ids of the tree edges in
+    /// the order they are picked, cheapest first and equal costs in list
+    /// order.
+    fn minimum_spanning_tree_ids(&self, edges: &[(T, T, W)]) -> Vec<usize> {
+        let mut parent: Vec<usize> = (0..self.vertices).collect();
+        fn root(parent: &mut [usize], mut node: usize) -> usize {
+            while parent[node] != node {
+                parent[node] = parent[parent[node]];
+                node = parent[node];
             }
+            node
         }
 
-        cost_sum
+        let mut ids = (0..edges.len()).collect::<Vec<_>>();
+        ids.sort_by_key(|id| TotalOrd(edges[*id].2));
+
+        let mut tree_ids = vec![];
+        for id in ids {
+            let (from, to, _) = edges[id];
+            let from_root = root(&mut parent, from.to_index());
+            let to_root = root(&mut parent, to.to_index());
+            if from_root != to_root {
+                parent[to_root] = from_root;
+                tree_ids.push(id);
+            }
+        }
+
+        tree_ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Path 0 - 1 - 2 whose two edges together overflow `W`.
+    fn overflowing_path<W: Numeric>(cost: W) -> Graph<u32, W> {
+        let mut graph = Graph::new(3);
+        graph.add_edge(0, 1, cost);
+        graph.add_edge(1, 2, cost);
+        graph
+    }
+
+    #[test]
+    fn saturated_paths_stay_reachable() {
+        let graph = overflowing_path(u8::MAX);
+        assert_eq!(
+            graph.shortest_path(0, 2),
+            Some((u8::MAX - 1, vec![0, 1, 2]))
+        );
+        assert_eq!(graph.shortest_path_tree(0).distance(2), Some(u8::MAX - 1));
+
+        let graph = overflowing_path(f64::MAX);
+        let (cost, _) = graph.shortest_path(0, 2).unwrap();
+        assert_eq!(cost, f64::MAX.next_down());
+        assert_eq!(graph.shortest_path_tree(0).distance(2), Some(cost));
+
+        assert_eq!(Graph::<u32, u8>::new(2).shortest_path(0, 1), None);
     }
 }
@@ -0,0 +1,118 @@
+//! Graphs whose edges carry several typed attributes (e.g. distance, travel
+//! time and capacity), stored as extra columns of a .gph file:
+//! `<from> <to> <attribute> <attribute> ...`. A `Graph` weighted by any one
+//! of them is obtained with `AttributedGraph::weighted_by`, so every
+//! algorithm can run on the attribute of choice.
+use std::{path::Path, str::FromStr};
+
+use super::{
+    parse_header, parse_node, FileBytes, GphLines, Graph, GraphError, NodeNames, NodeNo, Numeric,
+};
+
+/// Attributes parsed from the columns after the two node ids of an edge
+/// line. Implemented for tuples of up to four `FromStr + PartialEq` types,
+/// e.g. `(f64, u32, u32)` for distance, time and capacity.
+pub trait EdgeAttributes: Clone + Sized {
+    /// `None` when the column count or any value doesn't fit.
+    fn parse<'a>(columns: impl Iterator<Item = &'a str>) -> Option<Self>;
+    /// True when some value isn't equal to itself, i.e. a float NaN.
+    fn has_nan(&self) -> bool;
+}
+
+macro_rules! impl_edge_attributes {
+    ($($name: ident => $column: ident),+) => {
+        impl<$($name: FromStr + Clone + PartialEq),+> EdgeAttributes for ($($name,)+) {
+            fn parse<'a>(mut columns: impl Iterator<Item = &'a str>) -> Option<Self> {
+                let attributes = ($(columns.next()?.parse::<$name>().ok()?,)+);
+                columns.next().is_none().then_some(attributes)
+            }
+            fn has_nan(&self) -> bool {
+                let ($($column,)+) = self;
+                $($column != $column)||+
+            }
+        }
+    };
+}
+
+impl_edge_attributes!(A => a);
+impl_edge_attributes!(A => a, B => b);
+impl_edge_attributes!(A => a, B => b, C => c);
+impl_edge_attributes!(A => a, B => b, C => c, D => d);
+
+pub struct AttributedGraph<T: NodeNo, A: EdgeAttributes> {
+    edges: Vec<(T, T, A)>,
+    pub(super) vertices: usize,
+    pub(super) names: NodeNames,
+}
+
+impl<T: NodeNo, A: EdgeAttributes> AttributedGraph<T, A> {
+    pub fn new(vertices: usize) -> Self {
+        AttributedGraph {
+            edges: vec![],
+            vertices,
+            names: NodeNames::default(),
+        }
+    }
+
+    pub fn vertices(&self) -> usize {
+        self.vertices
+    }
+
+    pub fn add_edge(&mut self, from: T, to: T, attributes: A) {
+        self.edges.push((from, to, attributes));
+    }
+
+    /// Every undirected edge once, in insertion (file) order.
+    pub fn edges(&self) -> &[(T, T, A)] {
+        &self.edges
+    }
+
+    pub fn read_from_file(file_path: &str) -> Self {
+        Self::try_read_from_file(file_path)
+            .unwrap_or_else(|error| panic!("Can't load graph {}: {}", file_path, error))
+    }
+
+    pub fn try_read_from_file(file_path: &str) -> Result<Self, GraphError> {
+        let bytes = FileBytes::open(Path::new(file_path))?;
+        let mut lines = GphLines::new(&bytes);
+
+        let (header_line_no, graph_shape_line) = lines.header()?;
+        let (vertex_count, _) = parse_header(graph_shape_line, header_line_no)?;
+        let mut graph = AttributedGraph::new(vertex_count);
+
+        for line in lines {
+            let (line_no, line) = line?;
+            let mut values = line.split_ascii_whitespace();
+            let from = parse_node(values.next(), line_no, graph.vertices)?;
+            let to = parse_node(values.next(), line_no, graph.vertices)?;
+            let attributes = A::parse(values).ok_or(GraphError::InvalidEdge { line: line_no })?;
+            if attributes.has_nan() {
+                return Err(GraphError::NanWeight { line: line_no });
+            }
+            graph.add_edge(from, to, attributes);
+        }
+
+        Ok(graph)
+    }
+
+    /// Plain graph over the same nodes (and names), weighted by the chosen
+    /// attribute, e.g. `graph.weighted_by(|(distance, _, _)| *distance)`.
+    ///
+    /// Panics when the weight of an edge is NaN, which no algorithm can order.
+    pub fn weighted_by<W: Numeric>(&self, weight: impl Fn(&A) -> W) -> Graph<T, W> {
+        let mut graph = Graph::new(self.vertices);
+        for (from, to, attributes) in &self.edges {
+            let cost = weight(attributes);
+            assert!(
+                !cost.is_nan(),
+                "Edge {} - {} has a NaN weight",
+                *from + T::one(),
+                *to + T::one()
+            );
+            graph.add_edge(*from, *to, cost);
+        }
+        graph.names = self.names.clone();
+
+        graph
+    }
+}
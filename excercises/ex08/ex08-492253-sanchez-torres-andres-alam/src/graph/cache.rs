@@ -0,0 +1,299 @@
+//! Binary graph cache, so large graphs are parsed from text only once.
+//!
+//! Layout (version 2, little-endian):
+//!
+//! ```text
+//! "GPHCACHE" | version: u32 | weight type name: u8 length + bytes
+//! index width: u8 (4 or 8) | vertices: u64 | adjacency entries: u64
+//! header edge count of the text file: u64 (u64::MAX when unknown)
+//! row offsets: (vertices + 1) x u64
+//! targets: entries x index width | weights: entries x weight size
+//! checksum of everything above: u64
+//! ```
+//!
+//! The arrays are the adjacency lists in CSR form, in their exact order, so a
+//! loaded graph behaves exactly like the one that was saved. Files are memory
+//! mapped rather than read, see `FileBytes`.
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    ops::Deref,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use memmap2::Mmap;
+
+use super::{Edge, Graph, GraphError, NodeNo, Numeric};
+
+const MAGIC: &[u8; 8] = b"GPHCACHE";
+const VERSION: u32 = 2;
+const UNKNOWN_EDGES: u64 = u64::MAX;
+
+const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+/// FNV-1a over 8-byte words (the tail byte by byte), fast enough to check
+/// caches of hundreds of MB on every load.
+pub(super) fn checksum(bytes: &[u8]) -> u64 {
+    let words = bytes.chunks_exact(8);
+    let tail = words.remainder();
+    let hash = words.fold(FNV_OFFSET, |hash, word| {
+        (hash ^ u64::from_le_bytes(word.try_into().unwrap())).wrapping_mul(FNV_PRIME)
+    });
+    tail.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Whole file contents, memory mapped. Also used by the text parsers.
+///
+/// The bytes are only as stable as the file: another process truncating or
+/// rewriting it in place while it is mapped is undefined behaviour (a SIGBUS
+/// on unix). `write_atomically` replaces files by renaming, which leaves
+/// existing mappings intact, so only writers outside this crate can cause it.
+pub(super) struct FileBytes(Mmap);
+
+impl FileBytes {
+    pub(super) fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: read-only mapping; see the type docs for in-place writers
+        unsafe { Mmap::map(&file) }.map(FileBytes)
+    }
+}
+
+impl Deref for FileBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Bounds-checked reader over the cache bytes.
+pub(super) struct Cursor<'a> {
+    pub(super) bytes: &'a [u8],
+    pub(super) position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub(super) fn take(&mut self, len: usize) -> Result<&'a [u8], GraphError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(GraphError::InvalidCache("file is truncated"))?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    pub(super) fn u8(&mut self) -> Result<u8, GraphError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(super) fn u32(&mut self) -> Result<u32, GraphError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(super) fn u64(&mut self) -> Result<u64, GraphError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// `count` values of `width` bytes each.
+    pub(super) fn array(&mut self, count: u64, width: usize) -> Result<&'a [u8], GraphError> {
+        let len = usize::try_from(count)
+            .ok()
+            .and_then(|count| count.checked_mul(width))
+            .ok_or(GraphError::InvalidCache("file is truncated"))?;
+        self.take(len)
+    }
+}
+
+pub(super) fn read_index(bytes: &[u8], width: usize) -> u64 {
+    match width {
+        4 => u32::from_le_bytes(bytes.try_into().unwrap()) as u64,
+        _ => u64::from_le_bytes(bytes.try_into().unwrap()),
+    }
+}
+
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes next to the target and renames, so readers never see half a file.
+/// The temporary name is unique per process and call, so concurrent writers
+/// of the same file don't clobber each other's half-written data.
+pub(super) fn write_atomically(file_path: &str, bytes: &[u8]) -> io::Result<()> {
+    let temp_path = format!(
+        "{}.{}-{}.tmp",
+        file_path,
+        process::id(),
+        TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+    );
+    let mut file = File::create(&temp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    fs::rename(&temp_path, file_path)
+}
+
+/// Cache file used for `graph_path` and weight type `W`, e.g.
+/// `graphs/b01.u32.gphc` for `graphs/b01.gph`. The weight type is part of
+/// the name because the same text file may be loaded with several.
+pub fn cache_path<W: Numeric>(graph_path: &Path) -> PathBuf {
+    graph_path.with_extension(format!("{}.gphc", W::type_name()))
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    /// Saves the graph (without node names) in the binary cache format.
+    pub fn write_cache(&self, file_path: &str) -> io::Result<()> {
+        let entries = self.adjacencies.iter().map(Vec::len).sum::<usize>();
+        let index_width = if self.vertices <= u32::MAX as usize {
+            4
+        } else {
+            8
+        };
+
+        let mut bytes = Vec::with_capacity(
+            64 + 8 * (self.vertices + 1) + entries * (index_width + W::byte_size()),
+        );
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.push(W::type_name().len() as u8);
+        bytes.extend_from_slice(W::type_name().as_bytes());
+        bytes.push(index_width as u8);
+        bytes.extend_from_slice(&(self.vertices as u64).to_le_bytes());
+        bytes.extend_from_slice(&(entries as u64).to_le_bytes());
+        let declared_edges = self
+            .declared_edges
+            .map_or(UNKNOWN_EDGES, |edges| edges as u64);
+        bytes.extend_from_slice(&declared_edges.to_le_bytes());
+
+        let mut offset = 0u64;
+        bytes.extend_from_slice(&offset.to_le_bytes());
+        for adj_edges in &self.adjacencies {
+            offset += adj_edges.len() as u64;
+            bytes.extend_from_slice(&offset.to_le_bytes());
+        }
+        for edge in self.adjacencies.iter().flatten() {
+            let to = edge.to.to_index() as u64;
+            bytes.extend_from_slice(&to.to_le_bytes()[..index_width]);
+        }
+        for edge in self.adjacencies.iter().flatten() {
+            edge.cost.write_le_bytes(&mut bytes);
+        }
+        bytes.extend_from_slice(&checksum(&bytes).to_le_bytes());
+
+        write_atomically(file_path, &bytes)
+    }
+
+    /// Loads a graph saved by `write_cache`, rejecting files of another
+    /// version or weight type and corrupted ones.
+    pub fn try_read_cache(file_path: &str) -> Result<Self, GraphError> {
+        let bytes = FileBytes::open(Path::new(file_path))?;
+
+        if bytes.len() < MAGIC.len() + 8 || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(GraphError::InvalidCache("not a graph cache"));
+        }
+        let (body, stored_checksum) = bytes.split_at(bytes.len() - 8);
+        if checksum(body) != u64::from_le_bytes(stored_checksum.try_into().unwrap()) {
+            return Err(GraphError::InvalidCache("checksum mismatch"));
+        }
+
+        let mut cursor = Cursor {
+            bytes: body,
+            position: MAGIC.len(),
+        };
+        if cursor.u32()? != VERSION {
+            return Err(GraphError::InvalidCache("unsupported version"));
+        }
+        let name_len = cursor.u8()? as usize;
+        if cursor.take(name_len)? != W::type_name().as_bytes() {
+            return Err(GraphError::InvalidCache("different weight type"));
+        }
+        let index_width = cursor.u8()? as usize;
+        if index_width != 4 && index_width != 8 {
+            return Err(GraphError::InvalidCache("invalid index width"));
+        }
+        let vertices = usize::try_from(cursor.u64()?)
+            .map_err(|_| GraphError::InvalidCache("too many vertices"))?;
+        if vertices > 0 && vertices - 1 > T::max_value().to_index() {
+            return Err(GraphError::InvalidCache("node type too small"));
+        }
+        let entries = cursor.u64()?;
+        let declared_edges = match cursor.u64()? {
+            UNKNOWN_EDGES => None,
+            edges => Some(
+                usize::try_from(edges).map_err(|_| GraphError::InvalidCache("too many edges"))?,
+            ),
+        };
+
+        let offsets = cursor.array(vertices as u64 + 1, 8)?;
+        let targets = cursor.array(entries, index_width)?;
+        let weights = cursor.array(entries, W::byte_size())?;
+        if cursor.position != body.len() {
+            return Err(GraphError::InvalidCache("trailing bytes"));
+        }
+
+        let mut graph = Graph::new(vertices);
+        graph.declared_edges = declared_edges;
+        let mut offsets = offsets.chunks_exact(8).map(|offset| read_index(offset, 8));
+        let mut targets = targets.chunks_exact(index_width);
+        let mut weights = weights.chunks_exact(W::byte_size());
+        let mut start = offsets.next().unwrap();
+        if start != 0 {
+            return Err(GraphError::InvalidCache("invalid row offsets"));
+        }
+        for (node, adj_edges) in graph.adjacencies.iter_mut().enumerate() {
+            let end = offsets.next().unwrap();
+            if end < start || end > entries {
+                return Err(GraphError::InvalidCache("invalid row offsets"));
+            }
+
+            let from = T::from_index(node);
+            adj_edges.reserve_exact((end - start) as usize);
+            for _ in start..end {
+                let to = read_index(targets.next().unwrap(), index_width);
+                if to >= vertices as u64 {
+                    return Err(GraphError::InvalidCache("node out of range"));
+                }
+                adj_edges.push(Edge {
+                    from,
+                    to: T::from_index(to as usize),
+                    cost: W::read_le_bytes(weights.next().unwrap()),
+                });
+            }
+            start = end;
+        }
+        if start != entries {
+            return Err(GraphError::InvalidCache("invalid row offsets"));
+        }
+
+        Ok(graph)
+    }
+
+    /// Loads a .gph file through its binary cache (see `cache_path`): the
+    /// cache is used when it is newer than the text file and valid, and is
+    /// otherwise (re)built after parsing the text. Failing to write the cache
+    /// (e.g. a read-only directory) is not an error.
+    pub fn try_read_cached(file_path: &str) -> Result<Self, GraphError> {
+        let graph_path = Path::new(file_path);
+        let cache_file = cache_path::<W>(graph_path);
+        let cache_file = cache_file.to_str().unwrap();
+
+        let modified = |path: &str| fs::metadata(path).and_then(|metadata| metadata.modified());
+        let cache_is_fresh = match (modified(file_path), modified(cache_file)) {
+            (Ok(graph_time), Ok(cache_time)) => cache_time >= graph_time,
+            _ => false,
+        };
+        if cache_is_fresh {
+            if let Ok(graph) = Self::try_read_cache(cache_file) {
+                return Ok(graph);
+            }
+        }
+
+        let graph = Self::try_read_from_file(file_path)?;
+        let _ = graph.write_cache(cache_file);
+
+        Ok(graph)
+    }
+}
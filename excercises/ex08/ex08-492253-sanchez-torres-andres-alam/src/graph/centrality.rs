@@ -0,0 +1,218 @@
+//! Node importance measures. Every function returns one score per node,
+//! indexed by the 0-based node id.
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+};
+
+use super::{rng::Rng, Graph, NodeNo, Numeric, TotalOrd};
+
+/// Shortest path DAG from a single source, as needed by Brandes' algorithm.
+struct ShortestPathDag {
+    /// nodes in non-decreasing distance from the source
+    order: Vec<usize>,
+    predecessors: Vec<Vec<usize>>,
+    /// number of shortest paths from the source
+    path_count: Vec<f64>,
+    distance: Vec<f64>,
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    fn shortest_path_dag(&self, source: usize, weighted: bool) -> ShortestPathDag {
+        let mut dag = ShortestPathDag {
+            order: vec![],
+            predecessors: vec![vec![]; self.vertices],
+            path_count: vec![0.0; self.vertices],
+            distance: vec![f64::INFINITY; self.vertices],
+        };
+        dag.path_count[source] = 1.0;
+        dag.distance[source] = 0.0;
+
+        if !weighted {
+            let mut bfs_queue = VecDeque::from([source]);
+            while let Some(node) = bfs_queue.pop_front() {
+                dag.order.push(node);
+                for edge in &self.adjacencies[node] {
+                    let adj_node = edge.to.to_index();
+                    if dag.distance[adj_node].is_infinite() {
+                        dag.distance[adj_node] = dag.distance[node] + 1.0;
+                        bfs_queue.push_back(adj_node);
+                    }
+                    if dag.distance[adj_node] == dag.distance[node] + 1.0 {
+                        dag.path_count[adj_node] += dag.path_count[node];
+                        dag.predecessors[adj_node].push(node);
+                    }
+                }
+            }
+            return dag;
+        }
+
+        let mut settled = vec![false; self.vertices];
+        let mut nodes_q = BinaryHeap::from([(Reverse(TotalOrd(0.0)), source)]);
+        while let Some((Reverse(TotalOrd(cost)), node)) = nodes_q.pop() {
+            if settled[node] {
+                continue;
+            }
+            settled[node] = true;
+            dag.order.push(node);
+
+            for edge in &self.adjacencies[node] {
+                let adj_node = edge.to.to_index();
+                let new_cost = cost + edge.cost.to_f64();
+                if new_cost < dag.distance[adj_node] {
+                    dag.distance[adj_node] = new_cost;
+                    dag.path_count[adj_node] = dag.path_count[node];
+                    dag.predecessors[adj_node] = vec![node];
+                    nodes_q.push((Reverse(TotalOrd(new_cost)), adj_node));
+                } else if new_cost == dag.distance[adj_node] && !settled[adj_node] {
+                    dag.path_count[adj_node] += dag.path_count[node];
+                    dag.predecessors[adj_node].push(node);
+                }
+            }
+        }
+
+        dag
+    }
+
+    /// Degree divided by the maximum possible degree `|V| - 1`.
+    pub fn degree_centrality(&self) -> Vec<f64> {
+        let scale = if self.vertices > 1 {
+            1.0 / (self.vertices - 1) as f64
+        } else {
+            1.0
+        };
+
+        self.adjacencies
+            .iter()
+            .map(|adj_edges| adj_edges.len() as f64 * scale)
+            .collect()
+    }
+
+    /// Inverse of the average distance to the reachable nodes, scaled by the
+    /// fraction of the graph that is reachable (Wasserman–Faust), so nodes in
+    /// small components are not over-ranked.
+    pub fn closeness_centrality(&self, weighted: bool) -> Vec<f64> {
+        (0..self.vertices)
+            .map(|node| {
+                let dag = self.shortest_path_dag(node, weighted);
+                let reachable = dag.order.len() - 1;
+                let total_distance: f64 = dag.order.iter().map(|other| dag.distance[*other]).sum();
+
+                if reachable == 0 || total_distance == 0.0 {
+                    return 0.0;
+                }
+
+                let closeness = reachable as f64 / total_distance;
+                closeness * reachable as f64 / (self.vertices - 1) as f64
+            })
+            .collect()
+    }
+
+    fn accumulate_betweenness(&self, sources: &[usize], weighted: bool) -> Vec<f64> {
+        let mut betweenness = vec![0.0; self.vertices];
+        let mut dependency = vec![0.0; self.vertices];
+
+        for &source in sources {
+            let dag = self.shortest_path_dag(source, weighted);
+
+            for &node in &dag.order {
+                dependency[node] = 0.0;
+            }
+            for &node in dag.order.iter().rev() {
+                for &predecessor in &dag.predecessors[node] {
+                    dependency[predecessor] += dag.path_count[predecessor] / dag.path_count[node]
+                        * (1.0 + dependency[node]);
+                }
+                if node != source {
+                    betweenness[node] += dependency[node];
+                }
+            }
+        }
+
+        betweenness
+    }
+
+    fn normalize_betweenness(&self, betweenness: &mut [f64], source_scale: f64) {
+        // every pair is counted from both of its ends in an undirected graph
+        let pair_scale = if self.vertices > 2 {
+            1.0 / ((self.vertices - 1) * (self.vertices - 2)) as f64
+        } else {
+            1.0
+        };
+
+        for value in betweenness.iter_mut() {
+            *value *= pair_scale * source_scale;
+        }
+    }
+
+    /// Brandes' betweenness centrality, normalised to `[0, 1]`.
+    ///
+    /// O(|V||E|) unweighted and O(|V||E| + |V|^2 log |V|) weighted.
+    pub fn betweenness_centrality(&self, weighted: bool) -> Vec<f64> {
+        let sources = (0..self.vertices).collect::<Vec<_>>();
+        let mut betweenness = self.accumulate_betweenness(&sources, weighted);
+        self.normalize_betweenness(&mut betweenness, 1.0);
+
+        betweenness
+    }
+
+    /// Betweenness estimated from `samples` random source nodes instead of
+    /// all of them, extrapolated to the full graph.
+    pub fn approximate_betweenness_centrality(
+        &self,
+        weighted: bool,
+        samples: usize,
+        seed: u64,
+    ) -> Vec<f64> {
+        let mut sources = (0..self.vertices).collect::<Vec<_>>();
+        Rng::new(seed).shuffle(&mut sources);
+        sources.truncate(samples.max(1));
+
+        let mut betweenness = self.accumulate_betweenness(&sources, weighted);
+        self.normalize_betweenness(
+            &mut betweenness,
+            self.vertices as f64 / sources.len() as f64,
+        );
+
+        betweenness
+    }
+
+    /// PageRank of the random walk that follows each incident edge with equal
+    /// probability, iterated until the L1 change drops below `tolerance`.
+    /// Isolated nodes teleport uniformly.
+    pub fn pagerank(&self, damping: f64, tolerance: f64) -> Vec<f64> {
+        const MAX_ITERATIONS: usize = 1000;
+
+        let n = self.vertices as f64;
+        let mut rank = vec![1.0 / n; self.vertices];
+
+        for _ in 0..MAX_ITERATIONS {
+            let dangling_rank: f64 = (0..self.vertices)
+                .filter(|node| self.adjacencies[*node].is_empty())
+                .map(|node| rank[node])
+                .sum();
+            let mut next_rank =
+                vec![(1.0 - damping) / n + damping * dangling_rank / n; self.vertices];
+
+            for (node, adj_edges) in self.adjacencies.iter().enumerate() {
+                let share = damping * rank[node] / adj_edges.len() as f64;
+                for edge in adj_edges {
+                    next_rank[edge.to.to_index()] += share;
+                }
+            }
+
+            let change: f64 = rank
+                .iter()
+                .zip(&next_rank)
+                .map(|(old, new)| (old - new).abs())
+                .sum();
+            rank = next_rank;
+
+            if change < tolerance {
+                break;
+            }
+        }
+
+        rank
+    }
+}
@@ -0,0 +1,363 @@
+//! Vertex colouring: adjacent nodes must get different colours (numbered from
+//! 0). Self-loops and parallel edges are ignored, as a node never conflicts
+//! with itself.
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+use super::{Graph, GraphError, NodeNo, Numeric};
+
+/// Order in which the greedy colouring visits nodes.
+#[derive(Clone, Copy)]
+pub enum ColoringStrategy {
+    /// Decreasing degree (Welsh–Powell).
+    LargestFirst,
+    /// Reverse of repeatedly removing a minimum degree node; uses at most
+    /// degeneracy + 1 colours.
+    SmallestLast,
+    /// Next node is the one with most distinct neighbour colours, ties broken
+    /// by degree (Brélaz).
+    Dsatur,
+}
+
+fn smallest_free_color(colors: &[Option<usize>], neighbours: &[usize]) -> usize {
+    let mut taken = neighbours
+        .iter()
+        .filter_map(|adj_node| colors[*adj_node])
+        .collect::<Vec<_>>();
+    taken.sort_unstable();
+    taken.dedup();
+
+    taken
+        .iter()
+        .enumerate()
+        .find(|(color, taken_color)| color != *taken_color)
+        .map_or(taken.len(), |(color, _)| color)
+}
+
+fn smallest_last_order(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let mut degree = adjacency.iter().map(Vec::len).collect::<Vec<_>>();
+    let max_degree = degree.iter().copied().max().unwrap_or(0);
+    let mut buckets: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); max_degree + 1];
+    for (node, node_degree) in degree.iter().enumerate() {
+        buckets[*node_degree].insert(node);
+    }
+
+    let mut removed = vec![false; adjacency.len()];
+    let mut order = Vec::with_capacity(adjacency.len());
+    while order.len() < adjacency.len() {
+        let node = buckets
+            .iter_mut()
+            .find_map(|bucket| bucket.pop_first())
+            .unwrap();
+        removed[node] = true;
+        order.push(node);
+
+        for &adj_node in &adjacency[node] {
+            if !removed[adj_node] {
+                buckets[degree[adj_node]].remove(&adj_node);
+                degree[adj_node] -= 1;
+                buckets[degree[adj_node]].insert(adj_node);
+            }
+        }
+    }
+
+    order.reverse();
+    order
+}
+
+fn dsatur(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let mut colors = vec![None; adjacency.len()];
+    let mut neighbour_colors: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); adjacency.len()];
+    // (saturation, degree, node), the last entry is the next node to colour
+    let mut queue = (0..adjacency.len())
+        .map(|node| (0, adjacency[node].len(), node))
+        .collect::<BTreeSet<_>>();
+
+    while let Some((_, _, node)) = queue.pop_last() {
+        let color = smallest_free_color(&colors, &adjacency[node]);
+        colors[node] = Some(color);
+
+        for &adj_node in &adjacency[node] {
+            if colors[adj_node].is_none() && !neighbour_colors[adj_node].contains(&color) {
+                let key = (
+                    neighbour_colors[adj_node].len(),
+                    adjacency[adj_node].len(),
+                    adj_node,
+                );
+                queue.remove(&key);
+                neighbour_colors[adj_node].insert(color);
+                queue.insert((key.0 + 1, key.1, key.2));
+            }
+        }
+    }
+
+    colors.into_iter().map(Option::unwrap).collect()
+}
+
+fn color_count(colors: &[usize]) -> usize {
+    colors.iter().max().map_or(0, |color| color + 1)
+}
+
+/// State of the exact DSATUR branch and bound.
+struct ColoringSearch<'a> {
+    adjacency: &'a [Vec<usize>],
+    colors: Vec<Option<usize>>,
+    /// `conflicts[node][color]`: neighbours of `node` currently using `color`
+    conflicts: Vec<Vec<usize>>,
+    saturation: Vec<usize>,
+    best_colors: Vec<usize>,
+    best_count: usize,
+    lower_bound: usize,
+}
+
+impl ColoringSearch<'_> {
+    fn assign(&mut self, node: usize, color: usize) {
+        self.colors[node] = Some(color);
+        for &adj_node in &self.adjacency[node] {
+            if self.conflicts[adj_node][color] == 0 {
+                self.saturation[adj_node] += 1;
+            }
+            self.conflicts[adj_node][color] += 1;
+        }
+    }
+
+    fn unassign(&mut self, node: usize, color: usize) {
+        self.colors[node] = None;
+        for &adj_node in &self.adjacency[node] {
+            self.conflicts[adj_node][color] -= 1;
+            if self.conflicts[adj_node][color] == 0 {
+                self.saturation[adj_node] -= 1;
+            }
+        }
+    }
+
+    fn branch(&mut self, colored: usize, used_colors: usize) {
+        if self.best_count == self.lower_bound {
+            return;
+        }
+        if colored == self.adjacency.len() {
+            self.best_count = used_colors;
+            self.best_colors = self.colors.iter().map(|color| color.unwrap()).collect();
+            return;
+        }
+
+        let node = (0..self.adjacency.len())
+            .filter(|node| self.colors[*node].is_none())
+            .max_by_key(|node| (self.saturation[*node], self.adjacency[*node].len()))
+            .unwrap();
+
+        for color in 0..=used_colors {
+            if used_colors.max(color + 1) >= self.best_count {
+                break;
+            }
+            if self.conflicts[node][color] > 0 {
+                continue;
+            }
+
+            self.assign(node, color);
+            self.branch(colored + 1, used_colors.max(color + 1));
+            self.unassign(node, color);
+        }
+    }
+}
+
+/// Size of a greedily grown clique, a lower bound on the chromatic number.
+fn greedy_clique_size(adjacency: &[Vec<usize>]) -> usize {
+    let mut best = 0;
+    for start in 0..adjacency.len() {
+        let mut clique = vec![start];
+        let mut candidates = adjacency[start].clone();
+        candidates.sort_by_key(|node| std::cmp::Reverse(adjacency[*node].len()));
+
+        for candidate in candidates {
+            if clique
+                .iter()
+                .all(|member| adjacency[candidate].binary_search(member).is_ok())
+            {
+                clique.push(candidate);
+            }
+        }
+        best = best.max(clique.len());
+    }
+
+    best
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    /// Greedy colouring visiting nodes in the order given by `strategy`,
+    /// each taking the smallest colour not used by a neighbour.
+    pub fn greedy_coloring(&self, strategy: ColoringStrategy) -> Vec<usize> {
+        let adjacency = self.simple_adjacency();
+
+        let order = match strategy {
+            ColoringStrategy::LargestFirst => {
+                let mut order = (0..self.vertices).collect::<Vec<_>>();
+                order.sort_by_key(|node| std::cmp::Reverse(adjacency[*node].len()));
+                order
+            }
+            ColoringStrategy::SmallestLast => smallest_last_order(&adjacency),
+            ColoringStrategy::Dsatur => return dsatur(&adjacency),
+        };
+
+        let mut colors = vec![None; self.vertices];
+        for node in order {
+            colors[node] = Some(smallest_free_color(&colors, &adjacency[node]));
+        }
+
+        colors.into_iter().map(Option::unwrap).collect()
+    }
+
+    /// Whether `colors` assigns a colour to every node and no edge joins two
+    /// distinct nodes of the same colour.
+    pub fn is_valid_coloring(&self, colors: &[usize]) -> bool {
+        colors.len() == self.vertices
+            && self.adjacencies.iter().flatten().all(|edge| {
+                edge.from == edge.to || colors[edge.from.to_index()] != colors[edge.to.to_index()]
+            })
+    }
+
+    /// Exact chromatic number with an optimal colouring, by DSATUR branch and
+    /// bound seeded with the greedy DSATUR colouring and pruned by a clique
+    /// lower bound. Exponential in the worst case, meant for small graphs.
+    pub fn chromatic_number(&self) -> (usize, Vec<usize>) {
+        let adjacency = self.simple_adjacency();
+        let initial_colors = dsatur(&adjacency);
+        let initial_count = color_count(&initial_colors);
+
+        let mut search = ColoringSearch {
+            adjacency: &adjacency,
+            colors: vec![None; self.vertices],
+            conflicts: vec![vec![0; initial_count]; self.vertices],
+            saturation: vec![0; self.vertices],
+            best_colors: initial_colors,
+            best_count: initial_count,
+            lower_bound: greedy_clique_size(&adjacency),
+        };
+        search.branch(0, 0);
+
+        (search.best_count, search.best_colors)
+    }
+
+    /// Reads a DIMACS .col file (`c` comments, a `p edge <V> <E>` header and
+    /// `e <u> <v>` edges with 1-based ids). Every edge gets cost one.
+    pub fn try_read_from_dimacs(file_path: &str) -> Result<Self, GraphError> {
+        let reader = BufReader::new(File::open(file_path)?);
+        let mut graph: Option<Self> = None;
+
+        let mut line_no = 0;
+        for line in reader.lines() {
+            let line = line?;
+            line_no += 1;
+            let mut values = line.split_whitespace();
+
+            match values.next() {
+                Some("p") => {
+                    let vertices = values
+                        .nth(1)
+                        .and_then(|value| value.parse::<usize>().ok())
+                        .ok_or(GraphError::InvalidHeader { line: line_no })?;
+                    graph = Some(Graph::new(vertices));
+                }
+                Some("e") => {
+                    let graph = graph
+                        .as_mut()
+                        .ok_or(GraphError::InvalidHeader { line: line_no })?;
+                    let mut next_node = || -> Result<T, GraphError> {
+                        let raw_node = values
+                            .next()
+                            .ok_or(GraphError::InvalidEdge { line: line_no })?;
+                        let node = raw_node
+                            .parse::<usize>()
+                            .map_err(|_| GraphError::InvalidEdge { line: line_no })?;
+                        if node == 0 || node > graph.vertices {
+                            return Err(GraphError::NodeOutOfRange {
+                                line: line_no,
+                                node: raw_node.to_string(),
+                            });
+                        }
+                        Ok(T::from_index(node - 1))
+                    };
+                    let (from, to) = (next_node()?, next_node()?);
+                    graph.add_edge(from, to, W::one());
+                }
+                _ => {}
+            }
+        }
+
+        graph.ok_or(GraphError::InvalidHeader { line: line_no + 1 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::testing::generated_graphs, *};
+
+    fn cycle(length: u32) -> Graph<u32, u32> {
+        let mut graph = Graph::new(length as usize);
+        for node in 0..length {
+            graph.add_edge(node, (node + 1) % length, 1);
+        }
+        graph
+    }
+
+    fn complete(vertices: u32) -> Graph<u32, u32> {
+        let mut graph = Graph::new(vertices as usize);
+        for from in 0..vertices {
+            for to in from + 1..vertices {
+                graph.add_edge(from, to, 1);
+            }
+        }
+        graph
+    }
+
+    fn assert_chromatic_number(graph: &Graph<u32, u32>, expected: usize) {
+        let (count, colors) = graph.chromatic_number();
+        assert_eq!(count, expected);
+        assert!(graph.is_valid_coloring(&colors));
+        assert_eq!(color_count(&colors), count);
+    }
+
+    #[test]
+    fn cycles_need_two_or_three_colors() {
+        for length in 3..12 {
+            assert_chromatic_number(&cycle(length), if length % 2 == 1 { 3 } else { 2 });
+        }
+    }
+
+    #[test]
+    fn complete_graphs_need_a_color_per_node() {
+        for vertices in 1..9 {
+            assert_chromatic_number(&complete(vertices), vertices as usize);
+        }
+    }
+
+    #[test]
+    fn triangle_free_graph_can_need_four_colors() {
+        // Grötzsch graph: a 5-cycle, a copy of each node joined to its cycle
+        // neighbours, and a hub joined to the copies
+        let mut graph = Graph::new(11);
+        for node in 0..5 {
+            graph.add_edge(node, (node + 1) % 5, 1);
+            graph.add_edge(node + 5, (node + 1) % 5, 1);
+            graph.add_edge(node + 5, (node + 4) % 5, 1);
+            graph.add_edge(node + 5, 10, 1);
+        }
+        assert_chromatic_number(&graph, 4);
+    }
+
+    #[test]
+    fn greedy_colorings_are_valid() {
+        for graph in generated_graphs() {
+            for strategy in [
+                ColoringStrategy::LargestFirst,
+                ColoringStrategy::SmallestLast,
+                ColoringStrategy::Dsatur,
+            ] {
+                assert!(graph.is_valid_coloring(&graph.greedy_coloring(strategy)));
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//! Community detection on weighted undirected graphs, where edge costs are
+//! read as connection strengths.
+use std::collections::HashMap;
+
+use super::{rng::Rng, Graph, NodeNo, Numeric};
+
+/// Community id per node (numbered from 0 in order of first appearance) and
+/// the modularity of that partition.
+pub struct Communities {
+    pub labels: Vec<usize>,
+    pub modularity: f64,
+}
+
+/// Weighted adjacency where a self-loop entry holds the weight of both of its
+/// ends, so a node's degree is the plain sum of its entries.
+struct WeightedAdjacency {
+    adjacencies: Vec<Vec<(usize, f64)>>,
+}
+
+impl WeightedAdjacency {
+    fn degree(&self, node: usize) -> f64 {
+        self.adjacencies[node]
+            .iter()
+            .map(|(_, weight)| weight)
+            .sum()
+    }
+
+    /// Collapses every community into a single node.
+    fn aggregate(&self, community: &[usize], communities: usize) -> Self {
+        let mut merged: Vec<HashMap<usize, f64>> = vec![HashMap::new(); communities];
+        for (node, adj_edges) in self.adjacencies.iter().enumerate() {
+            for &(adj_node, weight) in adj_edges {
+                *merged[community[node]]
+                    .entry(community[adj_node])
+                    .or_insert(0.0) += weight;
+            }
+        }
+
+        let mut adjacencies = merged
+            .into_iter()
+            .map(|edges| edges.into_iter().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        // keep the iteration order independent of the hash seed
+        for adj_edges in adjacencies.iter_mut() {
+            adj_edges.sort_by_key(|(adj_node, _)| *adj_node);
+        }
+
+        WeightedAdjacency { adjacencies }
+    }
+}
+
+/// Renumbers labels from 0 in order of first appearance and returns the count.
+fn compact_labels(labels: &mut [usize]) -> usize {
+    let mut renamed = HashMap::new();
+    for label in labels.iter_mut() {
+        let next_label = renamed.len();
+        *label = *renamed.entry(*label).or_insert(next_label);
+    }
+
+    renamed.len()
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    fn weighted_adjacency(&self) -> WeightedAdjacency {
+        WeightedAdjacency {
+            adjacencies: self
+                .adjacencies
+                .iter()
+                .map(|adj_edges| {
+                    adj_edges
+                        .iter()
+                        .map(|edge| (edge.to.to_index(), edge.cost.to_f64()))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// Newman's weighted modularity of the partition given by `labels`.
+    /// A graph without edges has modularity 0.
+    pub fn modularity(&self, labels: &[usize]) -> f64 {
+        let communities = labels.iter().max().map_or(0, |label| label + 1);
+        let mut internal_weight = vec![0.0; communities];
+        let mut total_degree = vec![0.0; communities];
+        let mut double_total_weight = 0.0;
+
+        for (node, adj_edges) in self.adjacencies.iter().enumerate() {
+            for edge in adj_edges {
+                let weight = edge.cost.to_f64();
+                double_total_weight += weight;
+                total_degree[labels[node]] += weight;
+                if labels[node] == labels[edge.to.to_index()] {
+                    internal_weight[labels[node]] += weight;
+                }
+            }
+        }
+
+        if double_total_weight == 0.0 {
+            return 0.0;
+        }
+
+        internal_weight
+            .iter()
+            .zip(&total_degree)
+            .map(|(internal, degree)| {
+                internal / double_total_weight - (degree / double_total_weight).powi(2)
+            })
+            .sum()
+    }
+
+    /// Asynchronous label propagation: nodes repeatedly adopt the label with
+    /// the largest total edge weight among their neighbours, visiting nodes in
+    /// a random order and breaking ties randomly, until no label changes.
+    pub fn label_propagation(&self, seed: u64) -> Communities {
+        const MAX_ROUNDS: usize = 1000;
+
+        let mut rng = Rng::new(seed);
+        let mut labels = (0..self.vertices).collect::<Vec<_>>();
+        let mut order = labels.clone();
+        let mut label_weight: HashMap<usize, f64> = HashMap::new();
+
+        for _ in 0..MAX_ROUNDS {
+            rng.shuffle(&mut order);
+            let mut changed = false;
+
+            for &node in &order {
+                label_weight.clear();
+                for edge in &self.adjacencies[node] {
+                    if edge.to.to_index() != node {
+                        *label_weight
+                            .entry(labels[edge.to.to_index()])
+                            .or_insert(0.0) += edge.cost.to_f64();
+                    }
+                }
+
+                let Some(max_weight) = label_weight.values().copied().reduce(f64::max) else {
+                    continue;
+                };
+                if label_weight.get(&labels[node]) == Some(&max_weight) {
+                    continue;
+                }
+
+                let mut best_labels = label_weight
+                    .iter()
+                    .filter(|(_, weight)| **weight == max_weight)
+                    .map(|(label, _)| *label)
+                    .collect::<Vec<_>>();
+                best_labels.sort_unstable();
+
+                labels[node] = best_labels[rng.below(best_labels.len())];
+                changed = true;
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        compact_labels(&mut labels);
+        let modularity = self.modularity(&labels);
+
+        Communities { labels, modularity }
+    }
+
+    /// Louvain method: greedily moves nodes between neighbouring communities
+    /// while modularity improves, then collapses communities into single nodes
+    /// and repeats on the smaller graph until nothing moves.
+    pub fn louvain(&self) -> Communities {
+        let mut labels = (0..self.vertices).collect::<Vec<_>>();
+        let mut level = self.weighted_adjacency();
+
+        loop {
+            let nodes = level.adjacencies.len();
+            let degree = (0..nodes)
+                .map(|node| level.degree(node))
+                .collect::<Vec<_>>();
+            let double_total_weight: f64 = degree.iter().sum();
+            if double_total_weight == 0.0 {
+                break;
+            }
+
+            let mut community = (0..nodes).collect::<Vec<_>>();
+            let mut community_degree = degree.clone();
+            let mut weight_to = vec![0.0; nodes];
+            let mut touched = vec![];
+            let mut moved_any = false;
+
+            loop {
+                let mut moved = false;
+
+                for node in 0..nodes {
+                    let old_community = community[node];
+                    community_degree[old_community] -= degree[node];
+
+                    touched.clear();
+                    touched.push(old_community);
+                    for &(adj_node, weight) in &level.adjacencies[node] {
+                        if adj_node == node {
+                            continue;
+                        }
+                        let adj_community = community[adj_node];
+                        if weight_to[adj_community] == 0.0 {
+                            touched.push(adj_community);
+                        }
+                        weight_to[adj_community] += weight;
+                    }
+
+                    // modularity gain of joining a community, up to a constant factor
+                    let gain = |target: usize| {
+                        weight_to[target]
+                            - community_degree[target] * degree[node] / double_total_weight
+                    };
+
+                    let mut best_community = old_community;
+                    let mut best_gain = gain(old_community);
+                    for &candidate in &touched {
+                        let candidate_gain = gain(candidate);
+                        if candidate_gain > best_gain + 1e-12 {
+                            best_community = candidate;
+                            best_gain = candidate_gain;
+                        }
+                    }
+
+                    for &candidate in &touched {
+                        weight_to[candidate] = 0.0;
+                    }
+
+                    community_degree[best_community] += degree[node];
+                    if best_community != old_community {
+                        community[node] = best_community;
+                        moved = true;
+                    }
+                }
+
+                if !moved {
+                    break;
+                }
+                moved_any = true;
+            }
+
+            if !moved_any {
+                break;
+            }
+
+            let communities = compact_labels(&mut community);
+            for label in labels.iter_mut() {
+                *label = community[*label];
+            }
+            level = level.aggregate(&community, communities);
+        }
+
+        compact_labels(&mut labels);
+        let modularity = self.modularity(&labels);
+
+        Communities { labels, modularity }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{barbell, generated_graphs};
+
+    #[test]
+    fn louvain_splits_a_barbell_at_the_bridge() {
+        let graph = barbell::<f64>(5);
+        let communities = graph.louvain();
+        assert_eq!(communities.labels, [0, 0, 0, 0, 0, 1, 1, 1, 1, 1]);
+
+        // 21 edges, each clique holds 10 and has degree sum 21
+        let expected = 2.0 * (20.0 / 42.0 - (21.0 / 42.0_f64).powi(2));
+        assert!((communities.modularity - expected).abs() < 1e-12);
+        assert!(graph.modularity(&[0; 10]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reported_modularity_matches_labels() {
+        for graph in generated_graphs() {
+            for communities in [graph.louvain(), graph.label_propagation(1)] {
+                let modularity = graph.modularity(&communities.labels);
+                assert!((communities.modularity - modularity).abs() < 1e-9);
+                // numbered in order of first appearance
+                let mut next_label = 0;
+                for label in &communities.labels {
+                    assert!(*label <= next_label);
+                    next_label = next_label.max(label + 1);
+                }
+            }
+        }
+    }
+}
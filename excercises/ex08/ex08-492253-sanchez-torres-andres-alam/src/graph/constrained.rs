@@ -0,0 +1,325 @@
+//! Resource-constrained shortest paths on graphs whose edges carry a cost and
+//! a resource (e.g. price and travel time): the cheapest path whose resource
+//! stays within a budget, and the Pareto front of cost/resource trade-offs.
+//!
+//! Both run a label-setting search, the two-criteria version of
+//! `shortest_path`: a node may hold several labels (partial paths), one per
+//! non-dominated `(cost, resource)` pair, instead of a single distance.
+use std::collections::BinaryHeap;
+
+use super::{AttributedGraph, NodeNo, Numeric, SearchState, TotalOrd};
+
+/// A path with its total cost and resource use.
+pub struct ConstrainedPath<T: NodeNo, W: Numeric, R: Numeric> {
+    pub cost: W,
+    pub resource: R,
+    pub path: Vec<T>,
+}
+
+/// A partial path ending at `node`, like `PathDistance` but with both totals
+/// and the previous label instead of the previous node.
+#[derive(Clone, Copy)]
+struct Label<T: NodeNo, W: Numeric, R: Numeric> {
+    node: T,
+    cost: W,
+    resource: R,
+    parent: Option<usize>,
+}
+
+/// Heap entry for a label: its index as the node, keyed by cost and then
+/// resource.
+type LabelState<W, R> = SearchState<usize, (TotalOrd<W>, TotalOrd<R>)>;
+
+impl<T: NodeNo, W: Numeric, R: Numeric> AttributedGraph<T, (W, R)> {
+    /// Cheapest path from `source` to `target` whose resource is at most
+    /// `limit`, ties broken by the lower resource. `None` when no path fits
+    /// the budget.
+    pub fn constrained_shortest_path(
+        &self,
+        source: T,
+        target: T,
+        limit: R,
+    ) -> Option<ConstrainedPath<T, W, R>> {
+        self.label_setting(source, target, limit, true).pop()
+    }
+
+    /// Every non-dominated path from `source` to `target`, by increasing cost
+    /// (and so decreasing resource): no other path is at most as expensive
+    /// and uses at most as much resource. With a `limit`, only paths within
+    /// that budget. Empty when `target` is unreachable.
+    pub fn pareto_paths(
+        &self,
+        source: T,
+        target: T,
+        limit: Option<R>,
+    ) -> Vec<ConstrainedPath<T, W, R>> {
+        let limit = limit.unwrap_or_else(R::max_value);
+        self.label_setting(source, target, limit, false)
+    }
+
+    /// Labels are settled in (cost, resource) order, so a label is dominated
+    /// exactly when its node already has a settled label with no more
+    /// resource. Stops at the first label of `target` when `first_only`.
+    fn label_setting(
+        &self,
+        source: T,
+        target: T,
+        limit: R,
+        first_only: bool,
+    ) -> Vec<ConstrainedPath<T, W, R>> {
+        let mut adjacencies: Vec<Vec<(T, W, R)>> = vec![vec![]; self.vertices];
+        for (from, to, (cost, resource)) in self.edges() {
+            adjacencies[from.to_index()].push((*to, *cost, *resource));
+            adjacencies[to.to_index()].push((*from, *cost, *resource));
+        }
+
+        // least resource from each node to the target, so labels that can't
+        // finish within the budget are dropped early
+        let resource_to_target = self
+            .weighted_by(|(_, resource)| *resource)
+            .shortest_path_tree(target);
+        let fits = |node: T, resource: R| {
+            resource_to_target
+                .distance(node)
+                .is_some_and(|remaining| resource.saturating_add(remaining) <= limit)
+        };
+
+        // least resource of the labels settled at each node
+        let mut settled_resource: Vec<Option<R>> = vec![None; self.vertices];
+        let dominated = |settled_resource: &[Option<R>], node: T, resource: R| {
+            let settled = |node: T| settled_resource[node.to_index()];
+            // whatever follows a label using more than the target's best
+            // resource is dominated there too
+            settled(node).is_some_and(|settled| settled <= resource)
+                || settled(target).is_some_and(|settled| settled <= resource)
+        };
+
+        let mut labels = vec![];
+        let mut labels_q: BinaryHeap<LabelState<W, R>> = BinaryHeap::new();
+        let mut paths = vec![];
+        if fits(source, R::zero()) {
+            labels.push(Label {
+                node: source,
+                cost: W::zero(),
+                resource: R::zero(),
+                parent: None,
+            });
+            labels_q.push(SearchState {
+                node: 0,
+                key: (TotalOrd(W::zero()), TotalOrd(R::zero())),
+            });
+        }
+
+        while let Some(SearchState {
+            node: label,
+            key: (TotalOrd(cost), TotalOrd(resource)),
+        }) = labels_q.pop()
+        {
+            let node = labels[label].node;
+            if dominated(&settled_resource, node, resource) {
+                continue;
+            }
+            settled_resource[node.to_index()] = Some(resource);
+
+            if node == target {
+                paths.push(self.rebuild_label_path(&labels, label));
+                if first_only {
+                    break;
+                }
+                continue;
+            }
+
+            for (adj_node, edge_cost, edge_resource) in &adjacencies[node.to_index()] {
+                let new_cost = cost.saturating_add(*edge_cost);
+                let new_resource = resource.saturating_add(*edge_resource);
+
+                if fits(*adj_node, new_resource)
+                    && !dominated(&settled_resource, *adj_node, new_resource)
+                {
+                    labels_q.push(SearchState {
+                        node: labels.len(),
+                        key: (TotalOrd(new_cost), TotalOrd(new_resource)),
+                    });
+                    labels.push(Label {
+                        node: *adj_node,
+                        cost: new_cost,
+                        resource: new_resource,
+                        parent: Some(label),
+                    });
+                }
+            }
+        }
+
+        paths
+    }
+
+    fn rebuild_label_path(
+        &self,
+        labels: &[Label<T, W, R>],
+        label: usize,
+    ) -> ConstrainedPath<T, W, R> {
+        let mut path = vec![];
+        let mut current_label = Some(label);
+
+        while let Some(index) = current_label {
+            path.push(labels[index].node);
+            current_label = labels[index].parent;
+        }
+        path.reverse();
+
+        ConstrainedPath {
+            cost: labels[label].cost,
+            resource: labels[label].resource,
+            path,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::rng::Rng;
+    use super::*;
+
+    // small integer costs and resources, so many paths tie on one or both
+    fn graphs() -> Vec<AttributedGraph<u32, (u32, u32)>> {
+        (0..40)
+            .map(|seed| {
+                let mut rng = Rng::new(seed);
+                let vertices = 2 + rng.below(7);
+                let mut graph = AttributedGraph::new(vertices);
+                for from in 0..vertices as u32 {
+                    for to in from + 1..vertices as u32 {
+                        if rng.chance(0.45) {
+                            let attributes = (rng.below(4) as u32, rng.below(4) as u32);
+                            graph.add_edge(from, to, attributes);
+                        }
+                    }
+                }
+                graph
+            })
+            .collect()
+    }
+
+    // (cost, resource) of every simple path; removing a cycle never adds to
+    // either, so these include the best of all paths
+    fn simple_path_totals(
+        graph: &AttributedGraph<u32, (u32, u32)>,
+        source: u32,
+        target: u32,
+    ) -> Vec<(u32, u32)> {
+        fn visit(
+            graph: &AttributedGraph<u32, (u32, u32)>,
+            node: u32,
+            target: u32,
+            totals: (u32, u32),
+            on_path: &mut Vec<bool>,
+            found: &mut Vec<(u32, u32)>,
+        ) {
+            if node == target {
+                found.push(totals);
+                return;
+            }
+            on_path[node as usize] = true;
+            for (from, to, (cost, resource)) in graph.edges() {
+                let adj_node = match node {
+                    _ if *from == node => *to,
+                    _ if *to == node => *from,
+                    _ => continue,
+                };
+                if !on_path[adj_node as usize] {
+                    let totals = (totals.0 + cost, totals.1 + resource);
+                    visit(graph, adj_node, target, totals, on_path, found);
+                }
+            }
+            on_path[node as usize] = false;
+        }
+
+        let mut found = vec![];
+        let mut on_path = vec![false; graph.vertices()];
+        visit(graph, source, target, (0, 0), &mut on_path, &mut found);
+        found
+    }
+
+    // the path has to start and end right and add up to its totals
+    fn check_path(
+        graph: &AttributedGraph<u32, (u32, u32)>,
+        found: &ConstrainedPath<u32, u32, u32>,
+        source: u32,
+        target: u32,
+    ) {
+        assert_eq!(found.path.first(), Some(&source));
+        assert_eq!(found.path.last(), Some(&target));
+        let mut totals = (0, 0);
+        for step in found.path.windows(2) {
+            let (_, _, (cost, resource)) = graph
+                .edges()
+                .iter()
+                .find(|(from, to, _)| {
+                    (*from, *to) == (step[0], step[1]) || (*to, *from) == (step[0], step[1])
+                })
+                .unwrap();
+            totals = (totals.0 + cost, totals.1 + resource);
+        }
+        assert_eq!(totals, (found.cost, found.resource));
+    }
+
+    #[test]
+    fn constrained_shortest_path_matches_brute_force() {
+        for graph in graphs() {
+            let last = graph.vertices() as u32 - 1;
+            for (source, target) in [(0, last), (last, 1), (1, 1)] {
+                let totals = simple_path_totals(&graph, source, target);
+                for limit in 0..8 {
+                    let expected = totals
+                        .iter()
+                        .filter(|(_, resource)| *resource <= limit)
+                        .min()
+                        .copied();
+                    let found = graph.constrained_shortest_path(source, target, limit);
+                    if let Some(found) = &found {
+                        check_path(&graph, found, source, target);
+                    }
+                    assert_eq!(found.map(|found| (found.cost, found.resource)), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pareto_paths_match_brute_force() {
+        for graph in graphs() {
+            let last = graph.vertices() as u32 - 1;
+            for (source, target) in [(0, last), (last, 1), (1, 1)] {
+                let totals = simple_path_totals(&graph, source, target);
+                for limit in [None, Some(2), Some(4)] {
+                    let fitting = totals
+                        .iter()
+                        .filter(|(_, resource)| limit.is_none_or(|limit| *resource <= limit));
+                    let mut expected = fitting
+                        .clone()
+                        .filter(|(cost, resource)| {
+                            !fitting.clone().any(|other| {
+                                other.0 <= *cost
+                                    && other.1 <= *resource
+                                    && *other != (*cost, *resource)
+                            })
+                        })
+                        .copied()
+                        .collect::<Vec<_>>();
+                    expected.sort_unstable();
+                    expected.dedup();
+
+                    let found = graph.pareto_paths(source, target, limit);
+                    for path in &found {
+                        check_path(&graph, path, source, target);
+                    }
+                    let found = found
+                        .iter()
+                        .map(|path| (path.cost, path.resource))
+                        .collect::<Vec<_>>();
+                    assert_eq!(found, expected);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,326 @@
+//! Cycles of the undirected multigraph: a self-loop is a cycle of length one
+//! and two parallel edges form a cycle of length two.
+use std::collections::{HashMap, HashSet};
+
+use super::{Graph, NodeNo, Numeric, TotalOrd};
+
+/// Cycle given as its edges, each as stored in `Graph::edges`.
+pub struct Cycle<T: NodeNo, W: Numeric> {
+    pub edges: Vec<(T, T, W)>,
+    pub cost: W,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DfsState {
+    New,
+    OnStack,
+    Done,
+}
+
+/// Set of edge ids over GF(2), i.e. an element of the cycle space.
+#[derive(Clone)]
+struct EdgeSet(Vec<u64>);
+
+impl EdgeSet {
+    fn new(edges: usize) -> Self {
+        EdgeSet(vec![0; edges.div_ceil(64)])
+    }
+
+    fn toggle(&mut self, edge: usize) {
+        self.0[edge / 64] ^= 1 << (edge % 64);
+    }
+
+    fn xor(&mut self, other: &EdgeSet) {
+        for (word, other_word) in self.0.iter_mut().zip(&other.0) {
+            *word ^= other_word;
+        }
+    }
+
+    fn highest(&self) -> Option<usize> {
+        self.0
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, word)| **word != 0)
+            .map(|(index, word)| index * 64 + 63 - word.leading_zeros() as usize)
+    }
+
+    fn ids(&self) -> Vec<usize> {
+        (0..self.0.len() * 64)
+            .filter(|edge| self.0[edge / 64] & (1 << (edge % 64)) != 0)
+            .collect()
+    }
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    /// A graph is a forest exactly when `|E| = |V| - components`.
+    pub fn has_cycle(&self) -> bool {
+        self.edges().len() + self.connected_components() > self.vertices
+    }
+
+    /// Nodes of some cycle in traversal order (the last node links back to the
+    /// first), found by DFS. `None` for forests.
+    pub fn find_cycle(&self) -> Option<Vec<T>> {
+        // (adjacent node, edge id), so parallel edges are told apart
+        let mut incident = vec![vec![]; self.vertices];
+        for (id, (from, to, _)) in self.edges().into_iter().enumerate() {
+            incident[from.to_index()].push((to.to_index(), id));
+            if from != to {
+                incident[to.to_index()].push((from.to_index(), id));
+            }
+        }
+
+        let mut state = vec![DfsState::New; self.vertices];
+        for root in 0..self.vertices {
+            if state[root] != DfsState::New {
+                continue;
+            }
+
+            // (node, edge used to reach it, next incident edge to explore)
+            let mut stack: Vec<(usize, Option<usize>, usize)> = vec![(root, None, 0)];
+            state[root] = DfsState::OnStack;
+
+            while let Some(&mut (node, parent_edge, ref mut next)) = stack.last_mut() {
+                let Some(&(adj_node, edge)) = incident[node].get(*next) else {
+                    state[node] = DfsState::Done;
+                    stack.pop();
+                    continue;
+                };
+                *next += 1;
+
+                if Some(edge) == parent_edge {
+                    continue;
+                }
+                match state[adj_node] {
+                    DfsState::New => {
+                        state[adj_node] = DfsState::OnStack;
+                        stack.push((adj_node, Some(edge), 0));
+                    }
+                    DfsState::OnStack => {
+                        let start = stack
+                            .iter()
+                            .position(|(stack_node, _, _)| *stack_node == adj_node)
+                            .unwrap();
+                        return Some(
+                            stack[start..]
+                                .iter()
+                                .map(|(stack_node, _, _)| T::from_index(*stack_node))
+                                .collect(),
+                        );
+                    }
+                    DfsState::Done => {}
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Length in edges of the shortest cycle, `None` for forests. Runs a BFS
+    /// from every node, O(|V||E|).
+    pub fn girth(&self) -> Option<usize> {
+        let edges = self.edges();
+        if edges.iter().any(|(from, to, _)| from == to) {
+            return Some(1);
+        }
+        let mut pairs = edges
+            .iter()
+            .map(|(from, to, _)| (*from, *to))
+            .collect::<Vec<_>>();
+        let edge_count = pairs.len();
+        pairs.sort_by_key(|(from, to)| (from.to_index(), to.to_index()));
+        pairs.dedup();
+        if pairs.len() < edge_count {
+            return Some(2);
+        }
+
+        let adjacency = self.simple_adjacency();
+        let mut girth: Option<usize> = None;
+
+        for start in 0..self.vertices {
+            // only cycles shorter than the current best are worth finding
+            let max_depth = girth.map(|best| (best - 1) / 2);
+            let levels = self.bfs_levels(start, max_depth);
+
+            // an edge within a level closes an odd cycle through `start`, and
+            // a node with two parents an even one; from a node on a shortest
+            // cycle, that cycle is found with its exact length
+            for (node, adj_nodes) in adjacency.iter().enumerate() {
+                let Some(level) = levels[node] else {
+                    continue;
+                };
+                let mut parents = 0;
+                for &adj_node in adj_nodes {
+                    let length = match levels[adj_node] {
+                        Some(adj_level) if adj_level == level && node < adj_node => 2 * level + 1,
+                        Some(adj_level) if adj_level + 1 == level => {
+                            parents += 1;
+                            if parents < 2 {
+                                continue;
+                            }
+                            2 * level
+                        }
+                        _ => continue,
+                    };
+                    girth = Some(girth.map_or(length, |best| best.min(length)));
+                }
+            }
+        }
+
+        girth
+    }
+
+    /// Minimum weight cycle basis by Horton's algorithm: candidate cycles
+    /// made of an edge and the shortest paths from a node to its ends are
+    /// taken cheapest first whenever they are independent (Gaussian
+    /// elimination over GF(2)). Holds `|E| - |V| + components` cycles.
+    pub fn minimum_cycle_basis(&self) -> Vec<Cycle<T, W>> {
+        let edges = self.edges();
+        let dimension = edges.len() + self.connected_components() - self.vertices;
+        if dimension == 0 {
+            return vec![];
+        }
+
+        // cheapest edge between every adjacent pair, as used by the path trees
+        let mut cheapest: HashMap<(usize, usize), usize> = HashMap::new();
+        for (id, (from, to, cost)) in edges.iter().enumerate() {
+            let key = (from.to_index(), to.to_index());
+            match cheapest.get(&key) {
+                Some(best) if edges[*best].2.total_cmp(cost).is_le() => {}
+                _ => {
+                    cheapest.insert(key, id);
+                }
+            }
+        }
+        let edge_id = |a: usize, b: usize| cheapest[&(a.min(b), a.max(b))];
+
+        let mut candidates: Vec<(TotalOrd<W>, EdgeSet)> = vec![];
+        let mut seen = HashSet::new();
+        for root in 0..self.vertices {
+            let tree = self.shortest_path_tree(T::from_index(root));
+            let path = |node: usize| {
+                tree.path_to(T::from_index(node))
+                    .map(|nodes| nodes.iter().map(|node| node.to_index()).collect::<Vec<_>>())
+            };
+
+            for (id, (from, to, _)) in edges.iter().enumerate() {
+                let (Some(from_path), Some(to_path)) = (path(from.to_index()), path(to.to_index()))
+                else {
+                    continue;
+                };
+
+                // both paths may only share the root, or the candidate is not a simple cycle
+                let from_nodes = from_path[1..].iter().collect::<HashSet<_>>();
+                if to_path[1..].iter().any(|node| from_nodes.contains(node)) {
+                    continue;
+                }
+
+                let mut cycle = EdgeSet::new(edges.len());
+                for nodes in [&from_path, &to_path] {
+                    for pair in nodes.windows(2) {
+                        cycle.toggle(edge_id(pair[0], pair[1]));
+                    }
+                }
+                cycle.toggle(id);
+
+                let ids = cycle.ids();
+                if ids.is_empty() || !seen.insert(ids.clone()) {
+                    continue;
+                }
+                let mut cost = W::zero();
+                for id in ids {
+                    cost += edges[id].2;
+                }
+                candidates.push((TotalOrd(cost), cycle));
+            }
+        }
+        candidates.sort_by_key(|(cost, _)| *cost);
+
+        let mut pivots: HashMap<usize, EdgeSet> = HashMap::new();
+        let mut basis = vec![];
+        for (TotalOrd(cost), cycle) in candidates {
+            let mut reduced = cycle.clone();
+            while let Some(pivot) = reduced.highest() {
+                match pivots.get(&pivot) {
+                    Some(row) => reduced.xor(row),
+                    None => break,
+                }
+            }
+            let Some(pivot) = reduced.highest() else {
+                continue;
+            };
+            pivots.insert(pivot, reduced);
+
+            basis.push(Cycle {
+                edges: cycle.ids().into_iter().map(|id| edges[id]).collect(),
+                cost,
+            });
+            if basis.len() == dimension {
+                break;
+            }
+        }
+
+        basis
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::generate::{erdos_renyi_gnp, grid, WeightDistribution},
+        *,
+    };
+
+    fn graph_of(vertices: usize, edges: &[(u32, u32)]) -> Graph<u32, u32> {
+        let mut graph = Graph::new(vertices);
+        for (from, to) in edges {
+            graph.add_edge(*from, *to, 1);
+        }
+        graph
+    }
+
+    #[test]
+    fn girth_of_small_graphs() {
+        for length in 3..10 {
+            let edges = (0..length)
+                .map(|node| (node, (node + 1) % length))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                graph_of(length as usize, &edges).girth(),
+                Some(length as usize)
+            );
+        }
+        // Petersen graph: outer 5-cycle, inner pentagram and spokes
+        let petersen = (0..5)
+            .flat_map(|node| {
+                [
+                    (node, (node + 1) % 5),
+                    (node + 5, (node + 2) % 5 + 5),
+                    (node, node + 5),
+                ]
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(graph_of(10, &petersen).girth(), Some(5));
+
+        assert_eq!(graph_of(4, &[(0, 1), (1, 2), (1, 3)]).girth(), None);
+        assert_eq!(graph_of(2, &[(0, 1), (1, 1)]).girth(), Some(1));
+        assert_eq!(graph_of(2, &[(0, 1), (1, 0)]).girth(), Some(2));
+    }
+
+    #[test]
+    fn girth_matches_shortest_basis_cycle() {
+        let unit = WeightDistribution::Constant(1.0);
+        let graphs = (0..12)
+            .map(|seed| erdos_renyi_gnp::<u32, u32>(40, 0.02 * (seed % 4 + 1) as f64, &unit, seed))
+            .chain([grid(5, 6, &unit, 0), grid(1, 8, &unit, 0)]);
+        for graph in graphs {
+            // with unit costs a shortest cycle is in every minimum basis
+            let shortest = graph
+                .minimum_cycle_basis()
+                .iter()
+                .map(|cycle| cycle.edges.len())
+                .min();
+            assert_eq!(graph.girth(), shortest);
+        }
+    }
+}
@@ -0,0 +1,391 @@
+//! Connectivity and minimum spanning forest of a graph that changes one edge
+//! at a time, without reloading or recomputing it from scratch.
+use std::{cell::Cell, collections::VecDeque};
+
+use super::{Graph, NodeNo, Numeric, TotalOrd};
+
+/// Id of an inserted edge, used to remove it again.
+pub type EdgeId = usize;
+
+/// Edges of a graph split into a minimum spanning forest and the remaining
+/// edges, plus a union-find over the forest's trees.
+///
+/// Insertions take O(size of the touched tree) to find the most expensive
+/// edge on the cycle they close. Removing a non-forest edge is O(1);
+/// removing a forest edge scans all edges for the cheapest reconnection and
+/// rebuilds the union-find when none exists. `connected` is near-constant
+/// time.
+pub struct DynamicForest<T: NodeNo, W: Numeric> {
+    edges: Vec<Option<(T, T, W)>>,
+    in_forest: Vec<bool>,
+    // forest edge ids incident to each node
+    forest: Vec<Vec<EdgeId>>,
+    parent: Vec<Cell<usize>>,
+    size: Vec<usize>,
+    components: usize,
+    forest_cost: W,
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    /// Loads every edge (ids follow the order of `Graph::edges`) and picks
+    /// the forest by Kruskal's algorithm.
+    pub fn dynamic_forest(&self) -> DynamicForest<T, W> {
+        let mut forest = DynamicForest::new(self.vertices);
+        forest.edges = self.edges().into_iter().map(Some).collect();
+        forest.in_forest = vec![false; forest.edges.len()];
+
+        let mut ids = (0..forest.edges.len()).collect::<Vec<_>>();
+        ids.sort_by_key(|id| TotalOrd(forest.edge(*id).2));
+        for id in ids {
+            let (from, to, _) = forest.edge(id);
+            if forest.union(from.to_index(), to.to_index()) {
+                forest.link(id);
+            }
+        }
+
+        forest
+    }
+}
+
+impl<T: NodeNo, W: Numeric> DynamicForest<T, W> {
+    pub fn new(vertices: usize) -> Self {
+        DynamicForest {
+            edges: vec![],
+            in_forest: vec![],
+            forest: vec![vec![]; vertices],
+            parent: (0..vertices).map(Cell::new).collect(),
+            size: vec![1; vertices],
+            components: vertices,
+            forest_cost: W::zero(),
+        }
+    }
+
+    pub fn vertices(&self) -> usize {
+        self.forest.len()
+    }
+
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    /// Total cost of the minimum spanning forest, kept up to date on every
+    /// change (float costs may drift by rounding after many removals).
+    pub fn minimum_spanning_tree(&self) -> W {
+        self.forest_cost
+    }
+
+    pub fn minimum_spanning_tree_edges(&self) -> Vec<(T, T, W)> {
+        (0..self.edges.len())
+            .filter(|id| self.in_forest[*id])
+            .map(|id| self.edge(id))
+            .collect()
+    }
+
+    /// `false` for removed edges.
+    pub fn is_forest_edge(&self, id: EdgeId) -> bool {
+        self.in_forest.get(id).is_some_and(|in_forest| *in_forest)
+    }
+
+    pub fn connected(&self, from: T, to: T) -> bool {
+        self.find(from.to_index()) == self.find(to.to_index())
+    }
+
+    /// Adds an edge, swapping it into the forest when it joins two trees or
+    /// is cheaper than the most expensive edge on the cycle it closes.
+    pub fn insert_edge(&mut self, from: T, to: T, cost: W) -> EdgeId {
+        let id = self.edges.len();
+        self.edges.push(Some((from, to, cost)));
+        self.in_forest.push(false);
+
+        if self.union(from.to_index(), to.to_index()) {
+            self.link(id);
+            return id;
+        }
+
+        let heaviest = self
+            .forest_path(from.to_index(), to.to_index())
+            .into_iter()
+            .max_by_key(|path_id| TotalOrd(self.edge(*path_id).2));
+        if let Some(heaviest) = heaviest {
+            if TotalOrd(cost) < TotalOrd(self.edge(heaviest).2) {
+                self.cut(heaviest);
+                self.link(id);
+            }
+        }
+
+        id
+    }
+
+    /// Removes an edge, returning it, or `None` if it was already removed.
+    /// A removed forest edge is replaced by the cheapest edge reconnecting
+    /// both sides, if any.
+    pub fn remove_edge(&mut self, id: EdgeId) -> Option<(T, T, W)> {
+        let edge = (*self.edges.get(id)?)?;
+        let was_in_forest = self.in_forest[id];
+        if was_in_forest {
+            self.cut(id);
+        }
+        self.edges[id] = None;
+        if !was_in_forest {
+            return Some(edge);
+        }
+
+        let side = self.tree_nodes(edge.0.to_index());
+        let replacement = (0..self.edges.len())
+            .filter(|other| !self.in_forest[*other])
+            .filter_map(|other| self.edges[other].map(|other_edge| (other, other_edge)))
+            .filter(|(_, (from, to, _))| side[from.to_index()] != side[to.to_index()])
+            .min_by_key(|(_, (_, _, cost))| TotalOrd(*cost));
+
+        match replacement {
+            Some((other, _)) => self.link(other),
+            None => self.rebuild_components(),
+        }
+
+        Some(edge)
+    }
+
+    fn edge(&self, id: EdgeId) -> (T, T, W) {
+        self.edges[id].expect("Can't use a removed edge")
+    }
+
+    fn find(&self, node: usize) -> usize {
+        let mut root = node;
+        while self.parent[root].get() != root {
+            root = self.parent[root].get();
+        }
+
+        // path compression
+        let mut current_node = node;
+        while current_node != root {
+            current_node = self.parent[current_node].replace(root);
+        }
+
+        root
+    }
+
+    /// Joins the trees of both nodes by size, `false` if already joined.
+    fn union(&mut self, first: usize, second: usize) -> bool {
+        let (first, second) = (self.find(first), self.find(second));
+        if first == second {
+            return false;
+        }
+
+        let (small, large) = if self.size[first] < self.size[second] {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        self.parent[small].set(large);
+        self.size[large] += self.size[small];
+        self.components -= 1;
+
+        true
+    }
+
+    fn link(&mut self, id: EdgeId) {
+        let (from, to, cost) = self.edge(id);
+        self.in_forest[id] = true;
+        self.forest[from.to_index()].push(id);
+        self.forest[to.to_index()].push(id);
+        self.forest_cost += cost;
+    }
+
+    fn cut(&mut self, id: EdgeId) {
+        let (from, to, cost) = self.edge(id);
+        self.in_forest[id] = false;
+        self.forest[from.to_index()].retain(|forest_id| *forest_id != id);
+        self.forest[to.to_index()].retain(|forest_id| *forest_id != id);
+        self.forest_cost = self.forest_cost - cost;
+    }
+
+    fn other_end(&self, id: EdgeId, node: usize) -> usize {
+        let (from, to, _) = self.edge(id);
+        if from.to_index() == node {
+            to.to_index()
+        } else {
+            from.to_index()
+        }
+    }
+
+    /// Forest edge ids on the path between two nodes of the same tree.
+    fn forest_path(&self, from: usize, to: usize) -> Vec<EdgeId> {
+        let mut parent_edge: Vec<Option<EdgeId>> = vec![None; self.vertices()];
+        let mut visited = vec![false; self.vertices()];
+        let mut bfs_queue = VecDeque::from([from]);
+        visited[from] = true;
+
+        while let Some(current_node) = bfs_queue.pop_front() {
+            if current_node == to {
+                break;
+            }
+            for &id in &self.forest[current_node] {
+                let adj_node = self.other_end(id, current_node);
+                if !visited[adj_node] {
+                    visited[adj_node] = true;
+                    parent_edge[adj_node] = Some(id);
+                    bfs_queue.push_back(adj_node);
+                }
+            }
+        }
+
+        let mut path = vec![];
+        let mut current_node = to;
+        while let Some(id) = parent_edge[current_node] {
+            path.push(id);
+            current_node = self.other_end(id, current_node);
+        }
+
+        path
+    }
+
+    /// Marks the nodes of the forest tree containing `start`.
+    fn tree_nodes(&self, start: usize) -> Vec<bool> {
+        let mut visited = vec![false; self.vertices()];
+        let mut bfs_queue = VecDeque::from([start]);
+        visited[start] = true;
+
+        while let Some(current_node) = bfs_queue.pop_front() {
+            for &id in &self.forest[current_node] {
+                let adj_node = self.other_end(id, current_node);
+                if !visited[adj_node] {
+                    visited[adj_node] = true;
+                    bfs_queue.push_back(adj_node);
+                }
+            }
+        }
+
+        visited
+    }
+
+    /// Union-find can't split a set, so it is rebuilt from the forest.
+    fn rebuild_components(&mut self) {
+        for (node, parent) in self.parent.iter().enumerate() {
+            parent.set(node);
+        }
+        self.size = vec![1; self.vertices()];
+        self.components = self.vertices();
+
+        for id in 0..self.edges.len() {
+            if self.in_forest[id] {
+                let (from, to, _) = self.edge(id);
+                self.union(from.to_index(), to.to_index());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::rng::Rng, *};
+
+    /// Component of every node in the graph of the live edges, by the
+    /// smallest node of each component.
+    fn component_labels(graph: &Graph<u32, u32>) -> Vec<usize> {
+        let mut label: Vec<usize> = (0..graph.vertices()).collect();
+        fn root(label: &mut [usize], mut node: usize) -> usize {
+            while label[node] != node {
+                node = label[node];
+            }
+            node
+        }
+        for (from, to, _) in graph.minimum_spanning_tree_edges() {
+            let (from_root, to_root) = (
+                root(&mut label, from as usize),
+                root(&mut label, to as usize),
+            );
+            label[from_root.max(to_root)] = from_root.min(to_root);
+        }
+
+        (0..graph.vertices())
+            .map(|node| root(&mut label, node))
+            .collect()
+    }
+
+    fn assert_matches_fresh_forest(
+        forest: &DynamicForest<u32, u32>,
+        live: &[(u32, u32, u32)],
+        step: usize,
+    ) {
+        let mut graph = Graph::new(forest.vertices());
+        for &(from, to, cost) in live {
+            graph.add_edge(from, to, cost);
+        }
+
+        assert_eq!(
+            forest.minimum_spanning_tree(),
+            graph.minimum_spanning_tree(),
+            "forest weight after step {step}"
+        );
+        let forest_edges = forest.minimum_spanning_tree_edges();
+        assert_eq!(
+            forest_edges.iter().map(|(_, _, cost)| cost).sum::<u32>(),
+            forest.minimum_spanning_tree(),
+            "forest edges after step {step}"
+        );
+        assert_eq!(
+            forest_edges.len() + forest.components(),
+            forest.vertices(),
+            "forest edge count after step {step}"
+        );
+        assert_eq!(
+            forest.components(),
+            graph.connected_components(),
+            "components after step {step}"
+        );
+
+        let label = component_labels(&graph);
+        for from in 0..forest.vertices() {
+            for to in 0..forest.vertices() {
+                assert_eq!(
+                    forest.connected(from as u32, to as u32),
+                    label[from] == label[to],
+                    "connected({from}, {to}) after step {step}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn random_changes_match_fresh_minimum_spanning_forest() {
+        for seed in 0..10 {
+            let mut rng = Rng::new(seed);
+            let vertices = 25;
+            let mut forest = DynamicForest::new(vertices);
+            let mut live: Vec<(EdgeId, (u32, u32, u32))> = vec![];
+
+            for step in 0..400 {
+                // removals sometimes outpace insertions, so the graph keeps
+                // splitting and rejoining
+                let insert =
+                    live.is_empty() || rng.chance(if step % 100 < 60 { 0.7 } else { 0.35 });
+                if insert {
+                    let from = rng.below(vertices) as u32;
+                    let to = rng.below(vertices) as u32;
+                    let cost = rng.below(8) as u32;
+                    let id = forest.insert_edge(from, to, cost);
+                    live.push((id, (from, to, cost)));
+                } else {
+                    let (id, edge) = live.swap_remove(rng.below(live.len()));
+                    assert_eq!(forest.remove_edge(id), Some(edge));
+                    assert_eq!(forest.remove_edge(id), None);
+                }
+
+                let edges = live.iter().map(|(_, edge)| *edge).collect::<Vec<_>>();
+                assert_matches_fresh_forest(&forest, &edges, step);
+            }
+        }
+    }
+
+    #[test]
+    fn loaded_forest_matches_graph() {
+        for graph in super::super::testing::generated_graphs() {
+            let forest = graph.dynamic_forest();
+            assert_eq!(
+                forest.minimum_spanning_tree(),
+                graph.minimum_spanning_tree()
+            );
+            assert_eq!(forest.components(), graph.connected_components());
+        }
+    }
+}
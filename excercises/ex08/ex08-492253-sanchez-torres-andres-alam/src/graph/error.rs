@@ -0,0 +1,73 @@
+use std::{fmt, io};
+
+/// Reasons a graph file can't be loaded.
+#[derive(Debug)]
+pub enum GraphError {
+    Io(io::Error),
+    /// The first line is not `<vertices> <edges>`, or is missing (reported
+    /// on the line after the end of the file).
+    InvalidHeader {
+        line: usize,
+    },
+    /// An edge line is not `<from> <to> <weight>`.
+    InvalidEdge {
+        line: usize,
+    },
+    /// A node id outside `1..=vertices`.
+    NodeOutOfRange {
+        line: usize,
+        node: String,
+    },
+    NanWeight {
+        line: usize,
+    },
+    /// A .names line is not `<node> <name>`.
+    InvalidName {
+        line: usize,
+    },
+    /// A name given to two different nodes.
+    DuplicateName {
+        line: usize,
+        name: String,
+    },
+    /// A binary cache that is corrupted or was written for another version
+    /// or weight type.
+    InvalidCache(&'static str),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::Io(error) => write!(f, "Can't read graph file: {}", error),
+            GraphError::InvalidHeader { line } => {
+                write!(f, "Can't parse graph size on line {}", line)
+            }
+            GraphError::InvalidEdge { line } => {
+                write!(f, "Can't parse graph edge on line {}", line)
+            }
+            GraphError::NodeOutOfRange { line, node } => {
+                write!(f, "Node {} on line {} is out of range", node, line)
+            }
+            GraphError::NanWeight { line } => write!(f, "Edge weight on line {} is NaN", line),
+            GraphError::InvalidName { line } => {
+                write!(f, "Can't parse node name on line {}", line)
+            }
+            GraphError::DuplicateName { line, name } => {
+                write!(
+                    f,
+                    "Name {} on line {} is already used by another node",
+                    name, line
+                )
+            }
+            GraphError::InvalidCache(reason) => write!(f, "Can't use graph cache: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+impl From<io::Error> for GraphError {
+    fn from(error: io::Error) -> Self {
+        GraphError::Io(error)
+    }
+}
@@ -0,0 +1,226 @@
+//! Eulerian trails (every edge exactly once) and the Chinese postman route
+//! (every edge at least once, at minimum cost).
+use std::fmt;
+
+use super::{matching::min_weight_perfect_matching, Graph, NodeNo, Numeric};
+
+/// Why a graph has no Eulerian circuit or path. Nodes are reported 1-based,
+/// as in .gph files.
+#[derive(Debug)]
+pub enum EulerError<T: NodeNo> {
+    /// The edges are split over more than one connected component.
+    Disconnected { components: usize },
+    /// A circuit needs every node to have even degree.
+    OddDegreeCircuit { odd_nodes: Vec<T> },
+    /// A path needs zero or two nodes of odd degree.
+    OddDegreePath { odd_nodes: Vec<T> },
+}
+
+impl<T: NodeNo> fmt::Display for EulerError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let node_list = |nodes: &[T]| {
+            nodes
+                .iter()
+                .map(|node| (*node + T::one()).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match self {
+            EulerError::Disconnected { components } => write!(
+                f,
+                "The edges lie in {} separate components, but must all be connected",
+                components
+            ),
+            EulerError::OddDegreeCircuit { odd_nodes } => write!(
+                f,
+                "An Eulerian circuit needs every node to have even degree, but {} nodes have odd degree: {}",
+                odd_nodes.len(),
+                node_list(odd_nodes)
+            ),
+            EulerError::OddDegreePath { odd_nodes } => write!(
+                f,
+                "An Eulerian path needs 0 or 2 nodes of odd degree, but {} nodes have odd degree: {}",
+                odd_nodes.len(),
+                node_list(odd_nodes)
+            ),
+        }
+    }
+}
+
+impl<T: NodeNo + fmt::Debug> std::error::Error for EulerError<T> {}
+
+/// Closed walk covering every edge at least once.
+pub struct PostmanRoute<T: NodeNo, W: Numeric> {
+    pub edges: Vec<(T, T, W)>,
+    pub cost: W,
+}
+
+/// `(from, to, edge id)` step of a trail.
+type TrailStep = (usize, usize, usize);
+
+/// Hierholzer's algorithm on an edge list, returning the trail from `start`.
+/// Assumes the degree conditions hold.
+pub(super) fn hierholzer(nodes: usize, edges: &[(usize, usize)], start: usize) -> Vec<TrailStep> {
+    let mut incident = vec![vec![]; nodes];
+    for (id, (from, to)) in edges.iter().enumerate() {
+        incident[*from].push(id);
+        if from != to {
+            incident[*to].push(id);
+        }
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut next_incident = vec![0; nodes];
+    // (node, step that reached it)
+    let mut stack: Vec<(usize, Option<TrailStep>)> = vec![(start, None)];
+    let mut trail = vec![];
+
+    while let Some(&(node, arrival)) = stack.last() {
+        while next_incident[node] < incident[node].len()
+            && used[incident[node][next_incident[node]]]
+        {
+            next_incident[node] += 1;
+        }
+
+        match incident[node].get(next_incident[node]) {
+            Some(&id) => {
+                used[id] = true;
+                let (from, to) = edges[id];
+                let next_node = if from == node { to } else { from };
+                stack.push((next_node, Some((node, next_node, id))));
+            }
+            None => {
+                trail.extend(arrival);
+                stack.pop();
+            }
+        }
+    }
+
+    trail.reverse();
+    trail
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    fn odd_degree_nodes(&self) -> Vec<usize> {
+        (0..self.vertices)
+            .filter(|node| self.adjacencies[*node].len() % 2 == 1)
+            .collect()
+    }
+
+    /// Components that contain at least one edge.
+    fn edge_components(&self) -> usize {
+        let mut visited = vec![false; self.vertices];
+        let mut components = 0;
+
+        for node in 0..self.vertices {
+            if !visited[node] && !self.adjacencies[node].is_empty() {
+                self.bfs_depth_managed(node, &mut visited);
+                components += 1;
+            }
+        }
+
+        components
+    }
+
+    fn check_edges_connected(&self) -> Result<(), EulerError<T>> {
+        match self.edge_components() {
+            0 | 1 => Ok(()),
+            components => Err(EulerError::Disconnected { components }),
+        }
+    }
+
+    fn euler_trail(&self, start: usize) -> Vec<(T, T, W)> {
+        let edges = self.edges();
+        let pairs = edges
+            .iter()
+            .map(|(from, to, _)| (from.to_index(), to.to_index()))
+            .collect::<Vec<_>>();
+
+        hierholzer(self.vertices, &pairs, start)
+            .into_iter()
+            .map(|(from, to, id)| (T::from_index(from), T::from_index(to), edges[id].2))
+            .collect()
+    }
+
+    fn first_node_with_edges(&self) -> usize {
+        (0..self.vertices)
+            .find(|node| !self.adjacencies[*node].is_empty())
+            .unwrap_or(0)
+    }
+
+    /// Closed trail using every edge exactly once, as oriented `(from, to,
+    /// cost)` steps, by Hierholzer's algorithm in O(|E|).
+    pub fn eulerian_circuit(&self) -> Result<Vec<(T, T, W)>, EulerError<T>> {
+        self.check_edges_connected()?;
+
+        let odd_nodes = self.odd_degree_nodes();
+        if !odd_nodes.is_empty() {
+            return Err(EulerError::OddDegreeCircuit {
+                odd_nodes: odd_nodes.into_iter().map(T::from_index).collect(),
+            });
+        }
+
+        Ok(self.euler_trail(self.first_node_with_edges()))
+    }
+
+    /// Trail using every edge exactly once, starting at an odd degree node
+    /// when there are two of them (otherwise it is a circuit).
+    pub fn eulerian_path(&self) -> Result<Vec<(T, T, W)>, EulerError<T>> {
+        self.check_edges_connected()?;
+
+        let odd_nodes = self.odd_degree_nodes();
+        match odd_nodes.as_slice() {
+            [] => Ok(self.euler_trail(self.first_node_with_edges())),
+            [start, _] => Ok(self.euler_trail(*start)),
+            _ => Err(EulerError::OddDegreePath {
+                odd_nodes: odd_nodes.into_iter().map(T::from_index).collect(),
+            }),
+        }
+    }
+
+    /// Cheapest closed walk covering every edge: the odd degree nodes are
+    /// paired by a minimum weight perfect matching on shortest path distances
+    /// (exact for up to 20 odd nodes, greedy with local improvement above
+    /// that), the matched paths' edges are duplicated and an Eulerian circuit
+    /// of the result is returned.
+    pub fn chinese_postman(&self) -> Result<PostmanRoute<T, W>, EulerError<T>> {
+        self.check_edges_connected()?;
+
+        let odd_nodes = self.odd_degree_nodes();
+        let trees = odd_nodes
+            .iter()
+            .map(|node| self.shortest_path_tree(T::from_index(*node)))
+            .collect::<Vec<_>>();
+
+        let matching = min_weight_perfect_matching(odd_nodes.len(), |first, second| {
+            trees[first]
+                .distance(T::from_index(odd_nodes[second]))
+                .unwrap()
+        });
+
+        let mut augmented = Graph::new(self.vertices);
+        for (from, to, cost) in self.edges() {
+            augmented.add_edge(from, to, cost);
+        }
+        for (first, second) in matching {
+            let path = trees[first]
+                .path_to(T::from_index(odd_nodes[second]))
+                .unwrap();
+            for pair in path.windows(2) {
+                let cost = self
+                    .cheapest_edge(pair[0].to_index(), pair[1].to_index())
+                    .unwrap();
+                augmented.add_edge(pair[0], pair[1], cost);
+            }
+        }
+
+        let edges = augmented.euler_trail(augmented.first_node_with_edges());
+        let mut cost = W::zero();
+        for (_, _, edge_cost) in &edges {
+            cost += *edge_cost;
+        }
+
+        Ok(PostmanRoute { edges, cost })
+    }
+}
@@ -0,0 +1,133 @@
+//! Writers for `Graph`: the .gph format read by `Graph::read_from_file` and
+//! Graphviz DOT for rendering results.
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write as _,
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+use super::{Graph, NodeNo, Numeric};
+
+/// Nodes and edges to emphasise in a DOT rendering, e.g. a shortest path or a
+/// spanning tree.
+pub struct Highlight<T: NodeNo> {
+    nodes: Vec<T>,
+    edges: Vec<(T, T)>,
+}
+
+impl<T: NodeNo> Highlight<T> {
+    /// Highlights a node sequence as returned by `Graph::shortest_path`.
+    pub fn from_path(path: &[T]) -> Self {
+        Highlight {
+            nodes: path.to_vec(),
+            edges: path.windows(2).map(|pair| (pair[0], pair[1])).collect(),
+        }
+    }
+
+    /// Highlights an edge set as returned by `Graph::minimum_spanning_tree_edges`.
+    pub fn from_edges<W: Numeric>(edges: &[(T, T, W)]) -> Self {
+        Highlight {
+            nodes: edges
+                .iter()
+                .flat_map(|(from, to, _)| [*from, *to])
+                .collect(),
+            edges: edges.iter().map(|(from, to, _)| (*from, *to)).collect(),
+        }
+    }
+}
+
+fn edge_key<T: NodeNo>(from: T, to: T) -> (usize, usize) {
+    let (from, to) = (from.to_index(), to.to_index());
+    (from.min(to), from.max(to))
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    /// Writes the graph as a .gph file: a `V E` header followed by one
+    /// `from to cost` line per undirected edge, with 1-based node ids.
+    pub fn write_gph(&self, file_path: &str) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(file_path)?);
+        let edges = self.edges();
+
+        writeln!(writer, "{} {}", self.vertices, edges.len())?;
+        for (from, to, cost) in edges {
+            writeln!(writer, "{} {} {}", from + T::one(), to + T::one(), cost)?;
+        }
+
+        writer.flush()
+    }
+
+    /// Renders the graph in Graphviz DOT with 1-based node ids (labelled with
+    /// their names, if any) and edge costs as labels. Highlighted nodes and
+    /// edges are drawn in red; when parallel edges join a highlighted pair only
+    /// the cheapest one is marked.
+    pub fn to_dot(&self, highlight: Option<&Highlight<T>>) -> String {
+        let edges = self.edges();
+
+        let mut highlighted_nodes = HashSet::new();
+        let mut highlighted_edges = HashSet::new();
+        if let Some(highlight) = highlight {
+            highlighted_nodes.extend(highlight.nodes.iter().map(|node| node.to_index()));
+
+            let wanted = highlight
+                .edges
+                .iter()
+                .map(|(from, to)| edge_key(*from, *to))
+                .collect::<HashSet<_>>();
+
+            // index of the cheapest edge for every highlighted node pair
+            let mut cheapest: HashMap<(usize, usize), usize> = HashMap::new();
+            for (index, (from, to, cost)) in edges.iter().enumerate() {
+                let key = edge_key(*from, *to);
+                if !wanted.contains(&key) {
+                    continue;
+                }
+                match cheapest.get(&key) {
+                    Some(best) if edges[*best].2 <= *cost => {}
+                    _ => {
+                        cheapest.insert(key, index);
+                    }
+                }
+            }
+            highlighted_edges.extend(cheapest.into_values());
+        }
+
+        let mut dot = String::from("graph G {\n");
+
+        for node in 0..self.vertices {
+            let mut attributes = vec![];
+            if let Some(name) = self.names.name_of(node) {
+                attributes.push(format!("label=\"{}\"", name.replace('"', "\\\"")));
+            }
+            if highlighted_nodes.contains(&node) {
+                attributes.push("color=red".to_string());
+            }
+
+            if attributes.is_empty() {
+                writeln!(dot, "    {};", node + 1).unwrap();
+            } else {
+                writeln!(dot, "    {} [{}];", node + 1, attributes.join(", ")).unwrap();
+            }
+        }
+
+        for (index, (from, to, cost)) in edges.iter().enumerate() {
+            let style = if highlighted_edges.contains(&index) {
+                ", color=red, penwidth=2"
+            } else {
+                ""
+            };
+            writeln!(
+                dot,
+                "    {} -- {} [label=\"{}\"{}];",
+                *from + T::one(),
+                *to + T::one(),
+                cost,
+                style
+            )
+            .unwrap();
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
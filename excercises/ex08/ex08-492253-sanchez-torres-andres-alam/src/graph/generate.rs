@@ -0,0 +1,356 @@
+//! Seeded random graph generators for scale-testing the graph algorithms.
+//!
+//! Every generator takes a `seed`, so the same arguments always produce the
+//! same graph, and a `WeightDistribution` used to draw each edge cost.
+use std::collections::HashSet;
+
+use super::{rng::Rng, Graph, NodeNo, Numeric};
+
+pub enum WeightDistribution {
+    Constant(f64),
+    /// Continuous uniform in `[low, high)`.
+    Uniform {
+        low: f64,
+        high: f64,
+    },
+    /// Discrete uniform in `[low, high]`.
+    UniformInt {
+        low: i64,
+        high: i64,
+    },
+    Exponential {
+        mean: f64,
+    },
+    Normal {
+        mean: f64,
+        std_dev: f64,
+    },
+}
+
+impl WeightDistribution {
+    pub fn sample<W: Numeric>(&self, rng: &mut Rng) -> W {
+        let value = match *self {
+            WeightDistribution::Constant(value) => value,
+            WeightDistribution::Uniform { low, high } => low + (high - low) * rng.next_f64(),
+            WeightDistribution::UniformInt { low, high } => {
+                (low + rng.below((high - low + 1) as usize) as i64) as f64
+            }
+            WeightDistribution::Exponential { mean } => -mean * (1.0 - rng.next_f64()).ln(),
+            WeightDistribution::Normal { mean, std_dev } => {
+                // Box-Muller transform
+                let u1 = 1.0 - rng.next_f64();
+                let u2 = rng.next_f64();
+                mean + std_dev * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+            }
+        };
+
+        W::from_f64(value)
+    }
+}
+
+fn add_random_edge<T: NodeNo, W: Numeric>(
+    graph: &mut Graph<T, W>,
+    from: usize,
+    to: usize,
+    weights: &WeightDistribution,
+    rng: &mut Rng,
+) {
+    let cost = weights.sample(rng);
+    graph.add_edge(T::from_index(from), T::from_index(to), cost);
+}
+
+/// Erdős–Rényi G(n, p): every possible edge is present with probability `p`.
+///
+/// Uses the geometric skipping method of Batagelj and Brandes, so the running
+/// time is O(|V| + |E|) instead of O(|V|^2).
+pub fn erdos_renyi_gnp<T: NodeNo, W: Numeric>(
+    n: usize,
+    p: f64,
+    weights: &WeightDistribution,
+    seed: u64,
+) -> Graph<T, W> {
+    let mut rng = Rng::new(seed);
+    let mut graph = Graph::new(n);
+
+    if p <= 0.0 {
+        return graph;
+    }
+
+    let log_q = (1.0 - p).ln();
+    let (mut v, mut w): (usize, i64) = (1, -1);
+    while v < n {
+        let skip = if p >= 1.0 {
+            0
+        } else {
+            ((1.0 - rng.next_f64()).ln() / log_q).floor() as i64
+        };
+        w += 1 + skip;
+        while w >= v as i64 && v < n {
+            w -= v as i64;
+            v += 1;
+        }
+        if v < n {
+            add_random_edge(&mut graph, v, w as usize, weights, &mut rng);
+        }
+    }
+
+    graph
+}
+
+/// Erdős–Rényi G(n, m): `m` distinct edges chosen uniformly, without self-loops.
+pub fn erdos_renyi_gnm<T: NodeNo, W: Numeric>(
+    n: usize,
+    m: usize,
+    weights: &WeightDistribution,
+    seed: u64,
+) -> Graph<T, W> {
+    let max_edges = n * n.saturating_sub(1) / 2;
+    assert!(
+        m <= max_edges,
+        "G(n, m) can have at most {} edges",
+        max_edges
+    );
+
+    let mut rng = Rng::new(seed);
+    let mut graph = Graph::new(n);
+    let mut chosen = HashSet::new();
+
+    while chosen.len() < m {
+        let (from, to) = (rng.below(n), rng.below(n));
+        if from == to || !chosen.insert((from.min(to), from.max(to))) {
+            continue;
+        }
+        add_random_edge(&mut graph, from, to, weights, &mut rng);
+    }
+
+    graph
+}
+
+/// Barabási–Albert preferential attachment: each new node connects to `m`
+/// distinct existing nodes chosen proportionally to their degree.
+pub fn barabasi_albert<T: NodeNo, W: Numeric>(
+    n: usize,
+    m: usize,
+    weights: &WeightDistribution,
+    seed: u64,
+) -> Graph<T, W> {
+    assert!(m >= 1 && m < n, "Barabási–Albert needs 1 <= m < n");
+
+    let mut rng = Rng::new(seed);
+    let mut graph = Graph::new(n);
+
+    // every node appears once per incident edge, so uniform picks are degree biased
+    let mut repeated_nodes: Vec<usize> = vec![];
+    let mut targets: Vec<usize> = (0..m).collect();
+
+    for source in m..n {
+        for &target in &targets {
+            add_random_edge(&mut graph, source, target, weights, &mut rng);
+        }
+        repeated_nodes.extend(&targets);
+        repeated_nodes.extend(std::iter::repeat_n(source, m));
+
+        let mut next_targets = HashSet::new();
+        while next_targets.len() < m {
+            next_targets.insert(repeated_nodes[rng.below(repeated_nodes.len())]);
+        }
+        targets = next_targets.into_iter().collect();
+        targets.sort_unstable();
+    }
+
+    graph
+}
+
+/// Watts–Strogatz small world: a ring where every node links to its `k`
+/// nearest neighbours (`k` even), with each edge rewired with probability
+/// `beta` to a uniformly chosen node, avoiding self-loops and duplicates.
+pub fn watts_strogatz<T: NodeNo, W: Numeric>(
+    n: usize,
+    k: usize,
+    beta: f64,
+    weights: &WeightDistribution,
+    seed: u64,
+) -> Graph<T, W> {
+    assert!(
+        k.is_multiple_of(2) && k < n,
+        "Watts–Strogatz needs an even k < n"
+    );
+
+    let mut rng = Rng::new(seed);
+    let mut present = HashSet::new();
+    let mut edges = vec![];
+
+    for node in 0..n {
+        for offset in 1..=k / 2 {
+            let neighbour = (node + offset) % n;
+            present.insert((node.min(neighbour), node.max(neighbour)));
+            edges.push((node, neighbour));
+        }
+    }
+
+    for edge in edges.iter_mut() {
+        if !rng.chance(beta) {
+            continue;
+        }
+        let (from, to) = *edge;
+        let new_to = rng.below(n);
+        let key = (from.min(new_to), from.max(new_to));
+        if new_to == from || present.contains(&key) {
+            continue;
+        }
+        present.remove(&(from.min(to), from.max(to)));
+        present.insert(key);
+        *edge = (from, new_to);
+    }
+
+    let mut graph = Graph::new(n);
+    for (from, to) in edges {
+        add_random_edge(&mut graph, from, to, weights, &mut rng);
+    }
+
+    graph
+}
+
+/// Random geometric graph: nodes are uniform points in the unit square and
+/// are joined when their Euclidean distance is at most `radius`.
+pub fn random_geometric<T: NodeNo, W: Numeric>(
+    n: usize,
+    radius: f64,
+    weights: &WeightDistribution,
+    seed: u64,
+) -> Graph<T, W> {
+    let mut rng = Rng::new(seed);
+    let points = (0..n)
+        .map(|_| (rng.next_f64(), rng.next_f64()))
+        .collect::<Vec<_>>();
+
+    // bucket points into cells of side `radius` so only neighbouring cells are
+    // compared; at most about one cell per point, so tiny radii don't blow up
+    // memory (larger cells are still correct, just compare more points)
+    let max_cells_per_side = (n as f64).sqrt().ceil() as usize;
+    let cells_per_side =
+        ((1.0 / radius.max(f64::EPSILON)).floor() as usize).clamp(1, max_cells_per_side.max(1));
+    let cell_of =
+        |coordinate: f64| ((coordinate * cells_per_side as f64) as usize).min(cells_per_side - 1);
+    let mut cells = vec![vec![]; cells_per_side * cells_per_side];
+    for (node, (x, y)) in points.iter().enumerate() {
+        cells[cell_of(*y) * cells_per_side + cell_of(*x)].push(node);
+    }
+
+    let mut graph = Graph::new(n);
+    for (node, (x, y)) in points.iter().enumerate() {
+        let (cell_x, cell_y) = (cell_of(*x), cell_of(*y));
+        for near_y in cell_y.saturating_sub(1)..=(cell_y + 1).min(cells_per_side - 1) {
+            for near_x in cell_x.saturating_sub(1)..=(cell_x + 1).min(cells_per_side - 1) {
+                for &other in &cells[near_y * cells_per_side + near_x] {
+                    let (other_x, other_y) = points[other];
+                    if other > node && (x - other_x).hypot(y - other_y) <= radius {
+                        add_random_edge(&mut graph, node, other, weights, &mut rng);
+                    }
+                }
+            }
+        }
+    }
+
+    graph
+}
+
+fn lattice<T: NodeNo, W: Numeric>(
+    rows: usize,
+    cols: usize,
+    periodic: bool,
+    weights: &WeightDistribution,
+    seed: u64,
+) -> Graph<T, W> {
+    let mut rng = Rng::new(seed);
+    let mut graph = Graph::new(rows * cols);
+    let index = |row: usize, col: usize| row * cols + col;
+
+    for row in 0..rows {
+        for col in 0..cols {
+            if col + 1 < cols || (periodic && cols > 2) {
+                let next_col = (col + 1) % cols;
+                add_random_edge(
+                    &mut graph,
+                    index(row, col),
+                    index(row, next_col),
+                    weights,
+                    &mut rng,
+                );
+            }
+            if row + 1 < rows || (periodic && rows > 2) {
+                let next_row = (row + 1) % rows;
+                add_random_edge(
+                    &mut graph,
+                    index(row, col),
+                    index(next_row, col),
+                    weights,
+                    &mut rng,
+                );
+            }
+        }
+    }
+
+    graph
+}
+
+/// `rows x cols` grid where node `(r, c)` has index `r * cols + c`.
+pub fn grid<T: NodeNo, W: Numeric>(
+    rows: usize,
+    cols: usize,
+    weights: &WeightDistribution,
+    seed: u64,
+) -> Graph<T, W> {
+    lattice(rows, cols, false, weights, seed)
+}
+
+/// Grid with wrap-around edges in both directions. Dimensions of at most 2
+/// don't wrap, as that would only duplicate existing edges.
+pub fn torus<T: NodeNo, W: Numeric>(
+    rows: usize,
+    cols: usize,
+    weights: &WeightDistribution,
+    seed: u64,
+) -> Graph<T, W> {
+    lattice(rows, cols, true, weights, seed)
+}
+
+/// Uniformly random labelled tree, decoded from a random Prüfer sequence.
+pub fn random_tree<T: NodeNo, W: Numeric>(
+    n: usize,
+    weights: &WeightDistribution,
+    seed: u64,
+) -> Graph<T, W> {
+    let mut rng = Rng::new(seed);
+    let mut graph = Graph::new(n);
+
+    if n < 2 {
+        return graph;
+    }
+
+    let prufer = (0..n - 2).map(|_| rng.below(n)).collect::<Vec<_>>();
+    let mut degree = vec![1; n];
+    for &node in &prufer {
+        degree[node] += 1;
+    }
+
+    // linear time decoding: `leaf` is the smallest current leaf
+    let mut pointer = degree.iter().position(|&d| d == 1).unwrap();
+    let mut leaf = pointer;
+    for &node in &prufer {
+        add_random_edge(&mut graph, leaf, node, weights, &mut rng);
+        degree[node] -= 1;
+        if degree[node] == 1 && node < pointer {
+            leaf = node;
+        } else {
+            pointer += 1;
+            while degree[pointer] != 1 {
+                pointer += 1;
+            }
+            leaf = pointer;
+        }
+    }
+    add_random_edge(&mut graph, leaf, n - 1, weights, &mut rng);
+
+    graph
+}
@@ -0,0 +1,157 @@
+//! Streaming reader for .gph style text: walks the file bytes once, yielding
+//! the content of each line without allocating.
+//!
+//! Values may be separated by any ASCII whitespace (tabs, repeated or
+//! trailing spaces, `\r\n` line ends). Blank lines are skipped, and so are
+//! comments: everything after a `#`, and lines starting with `c` as in the
+//! DIMACS formats.
+
+use super::GraphError;
+
+pub(super) struct GphLines<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    line_no: usize,
+}
+
+impl<'a> GphLines<'a> {
+    pub(super) fn new(bytes: &'a [u8]) -> Self {
+        GphLines {
+            bytes,
+            position: 0,
+            line_no: 0,
+        }
+    }
+
+    /// The first line, which must be the header: a missing header is
+    /// reported on the line after the end of the file.
+    pub(super) fn header(&mut self) -> Result<(usize, &'a str), GraphError> {
+        match self.next() {
+            Some(Ok(line)) => Ok(line),
+            Some(Err(_)) => Err(GraphError::InvalidHeader { line: self.line_no }),
+            None => Err(GraphError::InvalidHeader {
+                line: self.line_no + 1,
+            }),
+        }
+    }
+}
+
+impl<'a> Iterator for GphLines<'a> {
+    /// 1-based line number and the line without its comment.
+    type Item = Result<(usize, &'a str), GraphError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position < self.bytes.len() {
+            let rest = &self.bytes[self.position..];
+            let line_len = rest
+                .iter()
+                .position(|byte| *byte == b'\n')
+                .unwrap_or(rest.len());
+            let mut line = &rest[..line_len];
+            self.position += line_len + 1;
+            self.line_no += 1;
+
+            if let Some(comment_start) = line.iter().position(|byte| *byte == b'#') {
+                line = &line[..comment_start];
+            }
+            let line = line.trim_ascii();
+            if line.is_empty() || line[0] == b'c' {
+                continue;
+            }
+
+            return Some(
+                std::str::from_utf8(line)
+                    .map(|line| (self.line_no, line))
+                    .map_err(|_| GraphError::InvalidEdge { line: self.line_no }),
+            );
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{parse_header, Graph},
+        *,
+    };
+
+    fn lines(text: &[u8]) -> Vec<(usize, &str)> {
+        GphLines::new(text).map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        let text = b"# size\n3 2\n\nc DIMACS style comment\n  c indented too\n1 2 5 # first\n#\n   \n2 3 1\n";
+        assert_eq!(lines(text), [(2, "3 2"), (6, "1 2 5"), (9, "2 3 1")]);
+    }
+
+    #[test]
+    fn trims_any_whitespace() {
+        let text = b"3\t2\r\n\t1  2\t5  \r\n\r\n2 3 1";
+        assert_eq!(lines(text), [(1, "3\t2"), (2, "1  2\t5"), (4, "2 3 1")]);
+        assert_eq!(parse_header("3\t2", 1).unwrap(), (3, 2));
+    }
+
+    #[test]
+    fn reports_invalid_text_on_its_line() {
+        let text = b"3 2\n1 2 5\n2 \xFF 1\n";
+        let mut lines = GphLines::new(text);
+        assert_eq!(lines.header().unwrap(), (1, "3 2"));
+        assert_eq!(lines.next().unwrap().unwrap(), (2, "1 2 5"));
+        assert!(matches!(
+            lines.next(),
+            Some(Err(GraphError::InvalidEdge { line: 3 }))
+        ));
+    }
+
+    #[test]
+    fn reports_header_errors_on_their_line() {
+        let mut lines = GphLines::new(b"# only comments\n\n");
+        assert!(matches!(
+            lines.header(),
+            Err(GraphError::InvalidHeader { line: 3 })
+        ));
+
+        let mut lines = GphLines::new(b"\n\xFF 2\n");
+        assert!(matches!(
+            lines.header(),
+            Err(GraphError::InvalidHeader { line: 2 })
+        ));
+
+        let mut lines = GphLines::new(b"# size\n3 2 1\n");
+        let (line_no, line) = lines.header().unwrap();
+        assert!(matches!(
+            parse_header(line, line_no),
+            Err(GraphError::InvalidHeader { line: 2 })
+        ));
+        assert!(matches!(
+            parse_header("3", 1),
+            Err(GraphError::InvalidHeader { line: 1 })
+        ));
+    }
+
+    #[test]
+    fn reports_edge_errors_on_their_line() {
+        let graph = Graph::<u32, u32>::new(3);
+        let text = b"3 3\n# edges\n1 2 5\n\n2 3\n3 9 1\n";
+        let mut lines = GphLines::new(text);
+        lines.header().unwrap();
+
+        let errors = lines
+            .map(|line| {
+                let (line_no, line) = line.unwrap();
+                graph.get_graph_entry(line, line_no).err()
+            })
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            errors[..],
+            [
+                None,
+                Some(GraphError::InvalidEdge { line: 5 }),
+                Some(GraphError::NodeOutOfRange { line: 6, .. })
+            ]
+        ));
+    }
+}
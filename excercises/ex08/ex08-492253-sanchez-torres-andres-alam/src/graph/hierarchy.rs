@@ -0,0 +1,826 @@
+//! Contraction hierarchies: a one-off preprocessing of a static graph after
+//! which point-to-point shortest paths only explore the nodes around the
+//! path instead of running a full Dijkstra per query.
+//!
+//! Nodes are contracted one at a time, least important first (by edge
+//! difference); contracting a node adds a shortcut between two of its
+//! neighbours whenever the path through it is the only shortest one. A query
+//! runs Dijkstra from the source over edges towards more important nodes
+//! only; the distance of any other node is then the cheapest way up from it
+//! to a node that search reached. With those the path is walked back from
+//! the target, taking at each node the parent `Graph::shortest_path` would,
+//! so both return the same path even between equally short ones (up to
+//! float rounding, see `ContractionHierarchy::shortest_path`).
+//!
+//! File layout (version 1, little-endian), see `cache` for the conventions:
+//!
+//! ```text
+//! "GPHCHIER" | version: u32 | weight type name: u8 length + bytes
+//! index width: u8 (4 or 8) | vertices: u64 | upward edges: u64
+//! row offsets: (vertices + 1) x u64
+//! targets: edges x index width | middle nodes: edges x index width
+//! weights: edges x weight size | checksum of everything above: u64
+//! ```
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::{BuildHasherDefault, Hasher},
+    io,
+    path::Path,
+};
+
+use super::{
+    cache::{checksum, read_index, write_atomically, Cursor, FileBytes},
+    dijkstra_parent, Graph, GraphError, NodeNo, Numeric, PathDistance, SearchState, TotalOrd,
+};
+
+const MAGIC: &[u8; 8] = b"GPHCHIER";
+const VERSION: u32 = 1;
+
+/// Nodes a witness search may settle before giving up (and adding the
+/// shortcut, which is never wrong, only possibly unnecessary).
+const WITNESS_SETTLE_LIMIT: usize = 100;
+
+/// An edge to a more important node: an original edge (the cheapest of any
+/// parallel ones) or a shortcut over `middle`, which is less important than
+/// both ends.
+#[derive(Clone, Copy)]
+struct UpwardEdge<T: NodeNo, W: Numeric> {
+    to: T,
+    cost: W,
+    middle: Option<T>,
+}
+
+/// Preprocessed graph answering point-to-point queries, see
+/// `Graph::contraction_hierarchy`.
+pub struct ContractionHierarchy<T: NodeNo, W: Numeric> {
+    vertices: usize,
+    // the upward edges of node `i` are `edges[offsets[i]..offsets[i + 1]]`
+    offsets: Vec<usize>,
+    edges: Vec<UpwardEdge<T, W>>,
+    // original edges from less important nodes as `(from, cost)`, derived
+    // from `edges` when built or loaded
+    down_edges: Vec<Vec<(T, W)>>,
+}
+
+/// Remaining graph while nodes are contracted, one edge per pair of nodes as
+/// `(neighbour, cost, middle node of a shortcut)`.
+struct Contraction<W: Numeric> {
+    neighbours: Vec<Vec<(usize, W, Option<usize>)>>,
+    contracted_neighbours: Vec<usize>,
+    // witness search state, reset after every search
+    witness_distance: Vec<W>,
+    witness_reached: Vec<usize>,
+    witness_q: BinaryHeap<SearchState<usize, TotalOrd<W>>>,
+}
+
+impl<W: Numeric> Contraction<W> {
+    fn new(vertices: usize) -> Self {
+        Contraction {
+            neighbours: vec![vec![]; vertices],
+            contracted_neighbours: vec![0; vertices],
+            witness_distance: vec![W::max_value(); vertices],
+            witness_reached: vec![],
+            witness_q: BinaryHeap::new(),
+        }
+    }
+
+    /// Adds the edge `from - to`, or lowers the cost of the existing one.
+    fn add_or_lower(&mut self, from: usize, to: usize, cost: W, middle: Option<usize>) {
+        for (node, adj_node) in [(from, to), (to, from)] {
+            let adj_edges = &mut self.neighbours[node];
+            match adj_edges
+                .iter_mut()
+                .find(|(other, _, _)| *other == adj_node)
+            {
+                Some(edge) if cost < edge.1 => *edge = (adj_node, cost, middle),
+                Some(_) => {}
+                None => adj_edges.push((adj_node, cost, middle)),
+            }
+        }
+    }
+
+    /// Bounded Dijkstra from `from` over the remaining graph without
+    /// `skipped`, filling `witness_distance` up to `max_cost`.
+    fn witness_search(&mut self, from: usize, skipped: usize, max_cost: W) {
+        for node in self.witness_reached.drain(..) {
+            self.witness_distance[node] = W::max_value();
+        }
+        self.witness_q.clear();
+
+        self.witness_distance[from] = W::zero();
+        self.witness_reached.push(from);
+        self.witness_q.push(SearchState {
+            node: from,
+            key: TotalOrd(W::zero()),
+        });
+        let mut settled = 0;
+        while let Some(SearchState {
+            node,
+            key: TotalOrd(cost),
+        }) = self.witness_q.pop()
+        {
+            if cost > self.witness_distance[node] {
+                continue;
+            }
+            settled += 1;
+            if cost > max_cost || settled > WITNESS_SETTLE_LIMIT {
+                break;
+            }
+
+            for (adj_node, edge_cost, _) in &self.neighbours[node] {
+                if *adj_node == skipped {
+                    continue;
+                }
+                let new_cost = cost.saturating_add(*edge_cost);
+                if new_cost < self.witness_distance[*adj_node] {
+                    if self.witness_distance[*adj_node] == W::max_value() {
+                        self.witness_reached.push(*adj_node);
+                    }
+                    self.witness_distance[*adj_node] = new_cost;
+                    self.witness_q.push(SearchState {
+                        node: *adj_node,
+                        key: TotalOrd(new_cost),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Shortcuts needed to contract `node`: `(from, to, cost)` for each pair
+    /// of neighbours without a witness path avoiding `node` that is at most
+    /// as short.
+    fn needed_shortcuts(&mut self, node: usize) -> Vec<(usize, usize, W)> {
+        let mut shortcuts = vec![];
+        let adj_edges = self.neighbours[node].clone();
+
+        for (index, (from, from_cost, _)) in adj_edges.iter().enumerate() {
+            let targets = &adj_edges[index + 1..];
+            let Some(max_cost) = targets
+                .iter()
+                .map(|(_, to_cost, _)| from_cost.saturating_add(*to_cost))
+                .max_by(|first, second| first.total_cmp(second))
+            else {
+                continue;
+            };
+
+            self.witness_search(*from, node, max_cost);
+            for (to, to_cost, _) in targets {
+                let via_cost = from_cost.saturating_add(*to_cost);
+                if self.witness_distance[*to] > via_cost {
+                    shortcuts.push((*from, *to, via_cost));
+                }
+            }
+        }
+
+        shortcuts
+    }
+
+    /// Edge difference (shortcuts added minus edges removed) plus contracted
+    /// neighbours, which spreads contraction evenly over the graph.
+    fn priority(&mut self, node: usize) -> (i64, Vec<(usize, usize, W)>) {
+        let shortcuts = self.needed_shortcuts(node);
+        let priority = shortcuts.len() as i64 - self.neighbours[node].len() as i64
+            + self.contracted_neighbours[node] as i64;
+        (priority, shortcuts)
+    }
+
+    /// Removes `node` from the remaining graph, returning its edges.
+    fn contract(
+        &mut self,
+        node: usize,
+        shortcuts: Vec<(usize, usize, W)>,
+    ) -> Vec<(usize, W, Option<usize>)> {
+        let adj_edges = std::mem::take(&mut self.neighbours[node]);
+        for (adj_node, _, _) in &adj_edges {
+            self.neighbours[*adj_node].retain(|(other, _, _)| *other != node);
+            self.contracted_neighbours[*adj_node] += 1;
+        }
+        for (from, to, cost) in shortcuts {
+            self.add_or_lower(from, to, cost, Some(node));
+        }
+
+        adj_edges
+    }
+}
+
+/// Hashes node indices with a single multiply; queries look up distances so
+/// often that the default hasher dominated their time.
+#[derive(Default)]
+struct NodeHasher(u64);
+
+impl Hasher for NodeHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0.rotate_left(5) ^ *byte as u64).wrapping_mul(0x517C_C1B7_2722_0A95);
+        }
+    }
+
+    fn write_usize(&mut self, index: usize) {
+        self.0 = (self.0.rotate_left(5) ^ index as u64).wrapping_mul(0x517C_C1B7_2722_0A95);
+    }
+}
+
+type NodeMap<V> = HashMap<usize, V, BuildHasherDefault<NodeHasher>>;
+type NodeSet = HashSet<usize, BuildHasherDefault<NodeHasher>>;
+
+/// State of one query: the upward search from the source, and distances
+/// and zero-cost hops (see `PathDistance`) of other nodes as they are needed.
+struct Query<'a, T: NodeNo, W: Numeric> {
+    hierarchy: &'a ContractionHierarchy<T, W>,
+    source: T,
+    // `(cost, previous node)` of the nodes reached going up from the source
+    upward: NodeMap<(W, T)>,
+    // `(cost, more important node it is reached through)`, `None` when the
+    // cost comes from `upward`
+    distance: NodeMap<(W, Option<T>)>,
+    zero_hops: NodeMap<usize>,
+}
+
+impl<'a, T: NodeNo, W: Numeric> Query<'a, T, W> {
+    /// Runs the whole upward search, without the stopping criteria of a
+    /// bidirectional query, since the distances of many nodes come from it.
+    fn new(hierarchy: &'a ContractionHierarchy<T, W>, source: T) -> Self {
+        let mut nodes_q = BinaryHeap::from([SearchState {
+            node: source,
+            key: TotalOrd(W::zero()),
+        }]);
+        let mut upward = NodeMap::default();
+        upward.insert(source.to_index(), (W::zero(), source));
+
+        while let Some(SearchState {
+            node,
+            key: TotalOrd(cost),
+        }) = nodes_q.pop()
+        {
+            if cost > upward[&node.to_index()].0 {
+                continue;
+            }
+            for edge in hierarchy.upward_edges(node.to_index()) {
+                let new_cost = cost.saturating_add(edge.cost);
+                let improves = upward
+                    .get(&edge.to.to_index())
+                    .is_none_or(|(adj_cost, _)| new_cost < *adj_cost);
+                if improves {
+                    upward.insert(edge.to.to_index(), (new_cost, node));
+                    nodes_q.push(SearchState {
+                        node: edge.to,
+                        key: TotalOrd(new_cost),
+                    });
+                }
+            }
+        }
+
+        Query {
+            hierarchy,
+            source,
+            upward,
+            distance: NodeMap::default(),
+            zero_hops: NodeMap::default(),
+        }
+    }
+
+    /// Distance from the source, `W::max_value()` when unreachable. A
+    /// shortest path goes up from the source and then down to `node`, so
+    /// this is the upward distance or the cheapest way through a more
+    /// important neighbour, whichever is lower.
+    fn distance(&mut self, node: T) -> W {
+        if let Some((cost, _)) = self.distance.get(&node.to_index()) {
+            return *cost;
+        }
+
+        let hierarchy = self.hierarchy;
+        // upward edges only lead to more important nodes, so this ends
+        let mut pending = vec![node.to_index()];
+        while let Some(current_node) = pending.last().copied() {
+            if self.distance.contains_key(&current_node) {
+                pending.pop();
+                continue;
+            }
+
+            let adj_edges = hierarchy.upward_edges(current_node);
+            let waiting = pending.len();
+            pending.extend(
+                adj_edges
+                    .iter()
+                    .map(|edge| edge.to.to_index())
+                    .filter(|adj_node| !self.distance.contains_key(adj_node)),
+            );
+            if pending.len() > waiting {
+                continue;
+            }
+
+            let mut best = self
+                .upward
+                .get(&current_node)
+                .map_or((W::max_value(), None), |(cost, _)| (*cost, None));
+            for edge in adj_edges {
+                let adj_cost = self.distance[&edge.to.to_index()].0;
+                if adj_cost == W::max_value() {
+                    continue;
+                }
+                let cost = adj_cost.saturating_add(edge.cost);
+                if cost < best.0 {
+                    best = (cost, Some(edge.to));
+                }
+            }
+            self.distance.insert(current_node, best);
+            pending.pop();
+        }
+
+        self.distance[&node.to_index()].0
+    }
+
+    /// Neighbours in the original graph whose distance continued over the
+    /// edge gives `node`'s distance.
+    fn tight_neighbours(&mut self, node: T) -> Vec<(T, W)> {
+        let cost = self.distance(node);
+        let mut neighbours = vec![];
+        for (adj_node, edge_cost) in self.hierarchy.original_neighbours(node) {
+            let adj_cost = self.distance(adj_node);
+            if adj_cost != W::max_value() && adj_cost.saturating_add(edge_cost) == cost {
+                neighbours.push((adj_node, edge_cost));
+            }
+        }
+        neighbours
+    }
+
+    /// Zero-cost hops Dijkstra ends up with at `node`: the fewest edges from
+    /// a node entered at a lower cost (or the source), over edges that don't
+    /// add to the cost. Found for all nodes of that cost connected that way
+    /// at once.
+    fn zero_hops(&mut self, node: T) -> usize {
+        if let Some(zero_hops) = self.zero_hops.get(&node.to_index()) {
+            return *zero_hops;
+        }
+
+        let cost = self.distance(node);
+        let mut plateau = vec![node];
+        let mut reached = NodeSet::default();
+        reached.insert(node.to_index());
+        let mut zero_edges = NodeMap::default();
+        let mut bfs_queue = VecDeque::new();
+        let mut index = 0;
+        while let Some(current_node) = plateau.get(index).copied() {
+            index += 1;
+            let mut entered = current_node == self.source;
+            let mut adj_nodes = vec![];
+            for (adj_node, _) in self.tight_neighbours(current_node) {
+                if self.distance(adj_node) < cost {
+                    entered = true;
+                } else {
+                    adj_nodes.push(adj_node);
+                    if reached.insert(adj_node.to_index()) {
+                        plateau.push(adj_node);
+                    }
+                }
+            }
+            if entered {
+                self.zero_hops.insert(current_node.to_index(), 0);
+                bfs_queue.push_back(current_node);
+            }
+            zero_edges.insert(current_node.to_index(), adj_nodes);
+        }
+
+        while let Some(current_node) = bfs_queue.pop_front() {
+            let hops = self.zero_hops[&current_node.to_index()];
+            for adj_node in &zero_edges[&current_node.to_index()] {
+                self.zero_hops
+                    .entry(adj_node.to_index())
+                    .or_insert_with(|| {
+                        bfs_queue.push_back(*adj_node);
+                        hops + 1
+                    });
+            }
+        }
+        // only float rounding in shortcut costs leaves nodes unreached,
+        // order them after all others
+        for current_node in plateau {
+            self.zero_hops
+                .entry(current_node.to_index())
+                .or_insert(reached.len());
+        }
+
+        self.zero_hops[&node.to_index()]
+    }
+
+    fn path_distance(&mut self, node: T) -> Option<PathDistance<T, W>> {
+        let cost = self.distance(node);
+        (cost != W::max_value()).then(|| PathDistance {
+            source: node,
+            cost,
+            zero_hops: self.zero_hops(node),
+        })
+    }
+
+    /// Path from `node` back to the source through the hierarchy edges its
+    /// distance came from, unpacked into original edges.
+    fn hierarchy_path(&self, node: T) -> Vec<T> {
+        let mut hops = vec![node];
+        let mut current_node = node;
+        while let Some(next_node) = self.distance[&current_node.to_index()].1 {
+            hops.push(next_node);
+            current_node = next_node;
+        }
+        while current_node != self.source {
+            current_node = self.upward[&current_node.to_index()].1;
+            hops.push(current_node);
+        }
+
+        let mut path = vec![node];
+        for pair in hops.windows(2) {
+            self.hierarchy.unpack_edge(pair[0], pair[1], &mut path);
+        }
+        path
+    }
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    /// Builds the contraction hierarchy of the graph. Worth it when many
+    /// queries run against the same graph; save it with `write_to_file` to
+    /// preprocess only once.
+    pub fn contraction_hierarchy(&self) -> ContractionHierarchy<T, W> {
+        let mut contraction = Contraction::new(self.vertices);
+        for (from, to, cost) in self.edges() {
+            // self-loops are never on a shortest path
+            if from != to {
+                contraction.add_or_lower(from.to_index(), to.to_index(), cost, None);
+            }
+        }
+
+        let mut order_q = (0..self.vertices)
+            .map(|node| Reverse((contraction.priority(node).0, node)))
+            .collect::<BinaryHeap<_>>();
+
+        // lazy updates: a node's priority is recomputed when it comes up, and
+        // it is only contracted if it is still the least important
+        let mut upward: Vec<Vec<UpwardEdge<T, W>>> = vec![vec![]; self.vertices];
+        while let Some(Reverse((_, node))) = order_q.pop() {
+            let (node_priority, shortcuts) = contraction.priority(node);
+            if let Some(Reverse((next_priority, _))) = order_q.peek() {
+                if node_priority > *next_priority {
+                    order_q.push(Reverse((node_priority, node)));
+                    continue;
+                }
+            }
+
+            for (adj_node, cost, middle) in contraction.contract(node, shortcuts) {
+                upward[node].push(UpwardEdge {
+                    to: T::from_index(adj_node),
+                    cost,
+                    middle: middle.map(T::from_index),
+                });
+            }
+        }
+
+        let mut offsets = vec![0];
+        for adj_edges in &upward {
+            offsets.push(offsets.last().unwrap() + adj_edges.len());
+        }
+
+        ContractionHierarchy::new(
+            self.vertices,
+            offsets,
+            upward.into_iter().flatten().collect(),
+        )
+    }
+}
+
+impl<T: NodeNo, W: Numeric> ContractionHierarchy<T, W> {
+    fn new(vertices: usize, offsets: Vec<usize>, edges: Vec<UpwardEdge<T, W>>) -> Self {
+        let mut down_edges = vec![vec![]; vertices];
+        for node in 0..vertices {
+            for edge in &edges[offsets[node]..offsets[node + 1]] {
+                if edge.middle.is_none() {
+                    down_edges[edge.to.to_index()].push((T::from_index(node), edge.cost));
+                }
+            }
+        }
+
+        ContractionHierarchy {
+            vertices,
+            offsets,
+            edges,
+            down_edges,
+        }
+    }
+
+    pub fn vertices(&self) -> usize {
+        self.vertices
+    }
+
+    fn upward_edges(&self, node: usize) -> &[UpwardEdge<T, W>] {
+        &self.edges[self.offsets[node]..self.offsets[node + 1]]
+    }
+
+    /// Neighbours over edges of the original graph, the cheapest of any
+    /// parallel ones. Edges replaced by a cheaper shortcut are left out, as
+    /// they are on no shortest path.
+    fn original_neighbours(&self, node: T) -> impl Iterator<Item = (T, W)> + '_ {
+        let upward = self
+            .upward_edges(node.to_index())
+            .iter()
+            .filter(|edge| edge.middle.is_none())
+            .map(|edge| (edge.to, edge.cost));
+        upward.chain(self.down_edges[node.to_index()].iter().copied())
+    }
+
+    /// The edge between two nodes, stored at the less important one.
+    fn edge_between(&self, first: T, second: T) -> UpwardEdge<T, W> {
+        let find = |from: T, to: T| {
+            self.upward_edges(from.to_index())
+                .iter()
+                .find(|edge| edge.to == to)
+                .copied()
+        };
+        find(first, second).or_else(|| find(second, first)).unwrap()
+    }
+
+    /// Expands the hierarchy edge `from - to` into original edges, appending
+    /// the nodes after `from` to `path`.
+    fn unpack_edge(&self, from: T, to: T, path: &mut Vec<T>) {
+        let mut pending = vec![(from, to)];
+        while let Some((from, to)) = pending.pop() {
+            match self.edge_between(from, to).middle {
+                // second half pushed first, so the first half comes out first
+                Some(middle) => pending.extend([(middle, to), (from, middle)]),
+                None => path.push(to),
+            }
+        }
+    }
+
+    /// Same distance and path as `Graph::shortest_path` on the graph this was
+    /// built from, also when several paths are equally short. With float
+    /// weights shortcut costs are sums taken in another order than
+    /// Dijkstra's, so where that rounding alone decides between two paths the
+    /// other one may come out.
+    pub fn shortest_path(&self, source: T, target: T) -> Option<(W, Vec<T>)> {
+        let mut query = Query::new(self, source);
+        if query.distance(target) == W::max_value() {
+            return None;
+        }
+
+        let mut path = vec![target];
+        let mut edge_costs = vec![];
+        let mut current_node = target;
+        while current_node != source {
+            let neighbours = query.tight_neighbours(current_node);
+            let parent = dijkstra_parent(current_node, neighbours.iter().copied(), |node| {
+                query.path_distance(node)
+            });
+            let Some(parent) = parent else {
+                // float rounding left no neighbour on a path as short, so
+                // go on along the hierarchy edges the distance came from
+                let mut rest = query.hierarchy_path(current_node);
+                for pair in rest.windows(2) {
+                    edge_costs.push(self.edge_between(pair[0], pair[1]).cost);
+                }
+                path.extend(rest.drain(1..));
+                break;
+            };
+            let (_, edge_cost) = neighbours.iter().find(|(node, _)| *node == parent).unwrap();
+            edge_costs.push(*edge_cost);
+            path.push(parent);
+            current_node = parent;
+        }
+        path.reverse();
+
+        // add up the original edges from the source, in the order Dijkstra
+        // does, so float distances round the same way
+        let cost = edge_costs
+            .iter()
+            .rev()
+            .fold(W::zero(), |cost, edge_cost| cost.saturating_add(*edge_cost));
+
+        Some((cost, path))
+    }
+
+    /// Saves the hierarchy in the binary format of the module docs.
+    pub fn write_to_file(&self, file_path: &str) -> io::Result<()> {
+        let index_width = if self.vertices < u32::MAX as usize {
+            4
+        } else {
+            8
+        };
+        // original edges have no middle node
+        let no_middle = self.vertices as u64;
+
+        let mut bytes = Vec::with_capacity(
+            64 + 8 * (self.vertices + 1) + self.edges.len() * (2 * index_width + W::byte_size()),
+        );
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.push(W::type_name().len() as u8);
+        bytes.extend_from_slice(W::type_name().as_bytes());
+        bytes.push(index_width as u8);
+        bytes.extend_from_slice(&(self.vertices as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.edges.len() as u64).to_le_bytes());
+
+        for offset in &self.offsets {
+            bytes.extend_from_slice(&(*offset as u64).to_le_bytes());
+        }
+        for edge in &self.edges {
+            let to = edge.to.to_index() as u64;
+            bytes.extend_from_slice(&to.to_le_bytes()[..index_width]);
+        }
+        for edge in &self.edges {
+            let middle = edge
+                .middle
+                .map_or(no_middle, |middle| middle.to_index() as u64);
+            bytes.extend_from_slice(&middle.to_le_bytes()[..index_width]);
+        }
+        for edge in &self.edges {
+            edge.cost.write_le_bytes(&mut bytes);
+        }
+        bytes.extend_from_slice(&checksum(&bytes).to_le_bytes());
+
+        write_atomically(file_path, &bytes)
+    }
+
+    /// Loads a hierarchy saved by `write_to_file`, rejecting files of another
+    /// version or weight type and corrupted ones.
+    pub fn try_read_from_file(file_path: &str) -> Result<Self, GraphError> {
+        let bytes = FileBytes::open(Path::new(file_path))?;
+
+        if bytes.len() < MAGIC.len() + 8 || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(GraphError::InvalidCache("not a contraction hierarchy"));
+        }
+        let (body, stored_checksum) = bytes.split_at(bytes.len() - 8);
+        if checksum(body) != u64::from_le_bytes(stored_checksum.try_into().unwrap()) {
+            return Err(GraphError::InvalidCache("checksum mismatch"));
+        }
+
+        let mut cursor = Cursor {
+            bytes: body,
+            position: MAGIC.len(),
+        };
+        if cursor.u32()? != VERSION {
+            return Err(GraphError::InvalidCache("unsupported version"));
+        }
+        let name_len = cursor.u8()? as usize;
+        if cursor.take(name_len)? != W::type_name().as_bytes() {
+            return Err(GraphError::InvalidCache("different weight type"));
+        }
+        let index_width = cursor.u8()? as usize;
+        if index_width != 4 && index_width != 8 {
+            return Err(GraphError::InvalidCache("invalid index width"));
+        }
+        let vertices = usize::try_from(cursor.u64()?)
+            .map_err(|_| GraphError::InvalidCache("too many vertices"))?;
+        if vertices > 0 && vertices - 1 > T::max_value().to_index() {
+            return Err(GraphError::InvalidCache("node type too small"));
+        }
+        let entries = cursor.u64()?;
+
+        let offsets = cursor.array(vertices as u64 + 1, 8)?;
+        let targets = cursor.array(entries, index_width)?;
+        let middles = cursor.array(entries, index_width)?;
+        let weights = cursor.array(entries, W::byte_size())?;
+        if cursor.position != body.len() {
+            return Err(GraphError::InvalidCache("trailing bytes"));
+        }
+
+        let offsets = offsets
+            .chunks_exact(8)
+            .map(|offset| read_index(offset, 8) as usize)
+            .collect::<Vec<_>>();
+        let offsets_valid = offsets[0] == 0
+            && offsets.windows(2).all(|pair| pair[0] <= pair[1])
+            && offsets[vertices] as u64 == entries;
+        if !offsets_valid {
+            return Err(GraphError::InvalidCache("invalid row offsets"));
+        }
+
+        let node = |bytes: &[u8]| {
+            let index = read_index(bytes, index_width);
+            (index < vertices as u64).then(|| T::from_index(index as usize))
+        };
+        let mut edges = Vec::with_capacity(entries as usize);
+        let rows = targets
+            .chunks_exact(index_width)
+            .zip(middles.chunks_exact(index_width))
+            .zip(weights.chunks_exact(W::byte_size()));
+        for ((to, middle), cost) in rows {
+            edges.push(UpwardEdge {
+                to: node(to).ok_or(GraphError::InvalidCache("node out of range"))?,
+                cost: W::read_le_bytes(cost),
+                middle: node(middle),
+            });
+        }
+
+        Ok(ContractionHierarchy::new(vertices, offsets, edges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fmt::Debug, fs, process};
+
+    use super::super::{
+        generate::{erdos_renyi_gnp, WeightDistribution},
+        testing::{bundled_graphs, generated_graphs},
+    };
+    use super::*;
+
+    fn assert_same_paths<W: Numeric + Debug>(
+        name: &str,
+        graph: &Graph<u32, W>,
+        hierarchy: &ContractionHierarchy<u32, W>,
+        sources: impl IntoIterator<Item = u32>,
+    ) {
+        for source in sources {
+            for target in 0..graph.vertices() as u32 {
+                assert_eq!(
+                    hierarchy.shortest_path(source, target),
+                    graph.shortest_path(source, target),
+                    "{} from {} to {}",
+                    name,
+                    source,
+                    target
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn shortest_path_matches_dijkstra() {
+        for (index, graph) in generated_graphs().iter().enumerate() {
+            let name = format!("generated graph {}", index);
+            assert_same_paths(
+                &name,
+                graph,
+                &graph.contraction_hierarchy(),
+                [0, 7, 150, 299],
+            );
+        }
+    }
+
+    // the weights are small integers, so float sums are exact and f64 paths
+    // have to match too; node 1 is the source of run_all.sh's 2 -> 45 query
+    #[test]
+    fn bundled_graphs_match_dijkstra() {
+        fn sources(graph: &Graph<u32, impl Numeric>) -> impl Iterator<Item = u32> {
+            (1..graph.vertices() as u32).step_by(8)
+        }
+        for (name, graph) in bundled_graphs::<u32>() {
+            assert_same_paths(
+                &name,
+                &graph,
+                &graph.contraction_hierarchy(),
+                sources(&graph),
+            );
+        }
+        for (name, graph) in bundled_graphs::<f64>() {
+            assert_same_paths(
+                &name,
+                &graph,
+                &graph.contraction_hierarchy(),
+                sources(&graph),
+            );
+        }
+    }
+
+    #[test]
+    fn shortest_path_matches_dijkstra_after_file_round_trip() {
+        let file_path = env::temp_dir().join(format!("hierarchy-test-{}.gphch", process::id()));
+        let file_path = file_path.to_str().unwrap();
+        for (index, graph) in generated_graphs().iter().enumerate() {
+            graph
+                .contraction_hierarchy()
+                .write_to_file(file_path)
+                .unwrap();
+            let hierarchy = ContractionHierarchy::try_read_from_file(file_path).unwrap();
+            let name = format!("generated graph {} after a round trip", index);
+            assert_same_paths(&name, graph, &hierarchy, [0, 7, 150, 299]);
+        }
+        fs::remove_file(file_path).unwrap();
+    }
+
+    // rounding in shortcut costs sends some of these queries down the
+    // fallback along hierarchy edges
+    #[test]
+    fn float_paths_are_shortest() {
+        let weights = WeightDistribution::Uniform {
+            low: 0.0,
+            high: 1.0,
+        };
+        let graph: Graph<u32, f64> = erdos_renyi_gnp(300, 0.02, &weights, 5);
+        let hierarchy = graph.contraction_hierarchy();
+        for source in [0, 150] {
+            for target in 0..graph.vertices() as u32 {
+                let (cost, path) = hierarchy.shortest_path(source, target).unwrap();
+                let (expected, _) = graph.shortest_path(source, target).unwrap();
+                assert!((cost - expected).abs() <= 1e-9 * expected.max(1.0));
+                assert_eq!((path[0], *path.last().unwrap()), (source, target));
+                for pair in path.windows(2) {
+                    let adj_edges = &graph.adjacencies[pair[0] as usize];
+                    assert!(adj_edges.iter().any(|edge| edge.to == pair[1]));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,394 @@
+//! Graph isomorphism (VF2 style search) and Weisfeiler–Lehman hashing, to
+//! tell whether two graphs are the same network up to node relabelling.
+//!
+//! With `weighted` the edge costs must match as well; otherwise only the
+//! edge multiplicities count. Self-loops and parallel edges are respected.
+use std::collections::BTreeMap;
+
+use super::{Graph, NodeNo, Numeric, TotalOrd};
+
+const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+/// Folds `value` into `hash` (FNV-1a over its bytes), so hashes are stable
+/// across runs and Rust versions.
+fn mix(hash: u64, value: u64) -> u64 {
+    value.to_le_bytes().iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Edge costs between every adjacent pair, sorted.
+struct PairLabels<W: Numeric> {
+    labels: Vec<BTreeMap<usize, Vec<TotalOrd<W>>>>,
+}
+
+impl<W: Numeric> PairLabels<W> {
+    fn get(&self, from: usize, to: usize) -> Option<&Vec<TotalOrd<W>>> {
+        self.labels[from].get(&to)
+    }
+}
+
+/// Search state of one side of the matching.
+struct MatchSide<'a, W: Numeric> {
+    pairs: &'a PairLabels<W>,
+    colors: &'a [u64],
+    core: Vec<Option<usize>>,
+    // mapped nodes among each node's neighbours, > 0 for the terminal set
+    mapped_neighbours: Vec<usize>,
+}
+
+impl<W: Numeric> MatchSide<'_, W> {
+    fn push(&mut self, node: usize, partner: usize) {
+        self.core[node] = Some(partner);
+        for adj_node in self.pairs.labels[node].keys() {
+            self.mapped_neighbours[*adj_node] += 1;
+        }
+    }
+
+    fn pop(&mut self, node: usize) {
+        self.core[node] = None;
+        for adj_node in self.pairs.labels[node].keys() {
+            self.mapped_neighbours[*adj_node] -= 1;
+        }
+    }
+
+    /// Unmapped neighbours of `node` (itself excluded) in and out of the
+    /// terminal set, and its mapped neighbours.
+    fn look_ahead(&self, node: usize) -> (usize, usize, usize) {
+        let mut counts = (0, 0, 0);
+        for adj_node in self.pairs.labels[node].keys() {
+            if *adj_node == node {
+                continue;
+            }
+            match (self.core[*adj_node], self.mapped_neighbours[*adj_node]) {
+                (Some(_), _) => counts.2 += 1,
+                (None, 0) => counts.1 += 1,
+                (None, _) => counts.0 += 1,
+            }
+        }
+        counts
+    }
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    fn pair_labels(&self, weighted: bool) -> PairLabels<W> {
+        let mut labels = vec![BTreeMap::new(); self.vertices];
+        for (from, to, cost) in self.edges() {
+            let cost = TotalOrd(if weighted { cost } else { W::zero() });
+            let (from, to) = (from.to_index(), to.to_index());
+            labels[from].entry(to).or_insert_with(Vec::new).push(cost);
+            if from != to {
+                labels[to].entry(from).or_insert_with(Vec::new).push(cost);
+            }
+        }
+        for node_labels in labels.iter_mut() {
+            for costs in node_labels.values_mut() {
+                costs.sort_unstable();
+            }
+        }
+
+        PairLabels { labels }
+    }
+
+    fn wl_initial_colors(&self) -> Vec<u64> {
+        (0..self.vertices)
+            .map(|node| mix(FNV_OFFSET, self.adjacencies[node].len() as u64))
+            .collect()
+    }
+
+    /// One refinement round: each node's colour is hashed with the sorted
+    /// (edge cost, colour) pairs of its neighbours.
+    fn wl_step(&self, colors: &[u64], weighted: bool) -> Vec<u64> {
+        (0..self.vertices)
+            .map(|node| {
+                let mut neighbourhood = self.adjacencies[node]
+                    .iter()
+                    .map(|edge| {
+                        let cost = if weighted {
+                            // -0.0 and 0.0 are the same cost
+                            (edge.cost.to_f64() + 0.0).to_bits()
+                        } else {
+                            0
+                        };
+                        (cost, colors[edge.to.to_index()])
+                    })
+                    .collect::<Vec<_>>();
+                neighbourhood.sort_unstable();
+
+                neighbourhood
+                    .into_iter()
+                    .fold(mix(FNV_OFFSET, colors[node]), |hash, (cost, color)| {
+                        mix(mix(hash, cost), color)
+                    })
+            })
+            .collect()
+    }
+
+    /// Weisfeiler–Lehman graph hash after `iterations` refinement rounds:
+    /// isomorphic graphs always get the same hash, and different graphs
+    /// rarely do (the test can't separate e.g. regular graphs of the same
+    /// degree). The value is stable across runs, so it can be stored.
+    pub fn weisfeiler_lehman_hash(&self, iterations: usize, weighted: bool) -> u64 {
+        let mut colors = self.wl_initial_colors();
+        let mut hash = mix(FNV_OFFSET, self.vertices as u64);
+
+        for round in 0..=iterations {
+            if round > 0 {
+                colors = self.wl_step(&colors, weighted);
+            }
+            let mut histogram = colors.clone();
+            histogram.sort_unstable();
+            hash = histogram.into_iter().fold(hash, mix);
+        }
+
+        hash
+    }
+
+    /// Whether some relabelling of the nodes turns `self` into `other`.
+    ///
+    /// Nodes are first split by Weisfeiler–Lehman colour refinement, run on
+    /// both graphs until neither partition gets finer; then a VF2 style
+    /// depth-first search extends a partial mapping one node at a time,
+    /// pruning by colour, edge labels to the mapped nodes and terminal set
+    /// sizes. Exponential in the worst case, fast on irregular graphs.
+    pub fn is_isomorphic(&self, other: &Graph<T, W>, weighted: bool) -> bool {
+        if self.vertices != other.vertices {
+            return false;
+        }
+        if self.edges().len() != other.edges().len() {
+            return false;
+        }
+
+        let histogram = |colors: &[u64]| {
+            let mut sorted = colors.to_vec();
+            sorted.sort_unstable();
+            sorted
+        };
+        let distinct = |colors: &[u64]| {
+            let mut sorted = histogram(colors);
+            sorted.dedup();
+            sorted.len()
+        };
+
+        let (mut colors, mut other_colors) = (self.wl_initial_colors(), other.wl_initial_colors());
+        loop {
+            if histogram(&colors) != histogram(&other_colors) {
+                return false;
+            }
+            let next_colors = self.wl_step(&colors, weighted);
+            let next_other_colors = other.wl_step(&other_colors, weighted);
+            let refined = distinct(&next_colors) > distinct(&colors)
+                || distinct(&next_other_colors) > distinct(&other_colors);
+            colors = next_colors;
+            other_colors = next_other_colors;
+            if !refined {
+                break;
+            }
+        }
+        if histogram(&colors) != histogram(&other_colors) {
+            return false;
+        }
+
+        let (pairs, other_pairs) = (self.pair_labels(weighted), other.pair_labels(weighted));
+        let side = |pairs, colors| MatchSide {
+            pairs,
+            colors,
+            core: vec![None; self.vertices],
+            mapped_neighbours: vec![0; self.vertices],
+        };
+        let mut first = side(&pairs, &colors);
+        let mut second = side(&other_pairs, &other_colors);
+
+        // match `other` in BFS order, rarest colours first, so every new node
+        // after the first of its component touches the mapped ones
+        let mut color_count = BTreeMap::new();
+        for color in &other_colors {
+            *color_count.entry(*color).or_insert(0) += 1;
+        }
+        let mut roots = (0..self.vertices).collect::<Vec<_>>();
+        roots.sort_by_key(|node| (color_count[&other_colors[*node]], *node));
+
+        let mut order = vec![];
+        let mut queued = vec![false; self.vertices];
+        for root in roots {
+            if queued[root] {
+                continue;
+            }
+            queued[root] = true;
+            let mut next = order.len();
+            order.push(root);
+            while next < order.len() {
+                let node = order[next];
+                next += 1;
+                for adj_node in other_pairs.labels[node].keys() {
+                    if !queued[*adj_node] {
+                        queued[*adj_node] = true;
+                        order.push(*adj_node);
+                    }
+                }
+            }
+        }
+
+        Self::extend_match(&mut first, &mut second, &order)
+    }
+
+    /// Maps the nodes of `other` in `order` depth first, trying the nodes of
+    /// `self` with the same colour as candidates. The search is as deep as
+    /// the graphs have nodes, so it keeps an explicit stack holding the next
+    /// candidate to try at each depth instead of recursing.
+    fn extend_match(first: &mut MatchSide<W>, second: &mut MatchSide<W>, order: &[usize]) -> bool {
+        let mut classes = BTreeMap::new();
+        for (node, color) in first.colors.iter().enumerate() {
+            classes.entry(*color).or_insert_with(Vec::new).push(node);
+        }
+        // index into the colour class of the node at each depth
+        let mut next_candidate = vec![0];
+
+        while let Some(&start) = next_candidate.last() {
+            let depth = next_candidate.len() - 1;
+            let Some(&node) = order.get(depth) else {
+                return true;
+            };
+
+            let class = &classes[&second.colors[node]];
+            let candidate = (start..class.len()).find(|index| {
+                let candidate = class[*index];
+                first.core[candidate].is_none()
+                    && (first.mapped_neighbours[candidate] > 0)
+                        == (second.mapped_neighbours[node] > 0)
+                    && Self::feasible(first, second, candidate, node)
+            });
+            match candidate {
+                Some(index) => {
+                    first.push(class[index], node);
+                    second.push(node, class[index]);
+                    next_candidate[depth] = index + 1;
+                    next_candidate.push(0);
+                }
+                None => {
+                    // backtrack: undo the previous node's mapping and try its
+                    // next candidate
+                    next_candidate.pop();
+                    if depth > 0 {
+                        let previous = order[depth - 1];
+                        let partner = second.core[previous].expect("Mapped on the way down");
+                        first.pop(partner);
+                        second.pop(previous);
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    fn feasible(
+        first: &MatchSide<W>,
+        second: &MatchSide<W>,
+        candidate: usize,
+        node: usize,
+    ) -> bool {
+        if first.pairs.get(candidate, candidate) != second.pairs.get(node, node) {
+            return false;
+        }
+
+        // every mapped neighbour of `node` must be matched by one of `candidate`
+        for (adj_node, costs) in &second.pairs.labels[node] {
+            if let Some(partner) = second.core[*adj_node] {
+                if first.pairs.get(candidate, partner) != Some(costs) {
+                    return false;
+                }
+            }
+        }
+
+        // equal counts then rule out extra mapped neighbours of `candidate`
+        first.look_ahead(candidate) == second.look_ahead(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            generate::{erdos_renyi_gnm, WeightDistribution},
+            rng::Rng,
+            testing::{bundled_graphs, generated_graphs},
+        },
+        *,
+    };
+
+    /// Same edges under a random node permutation, listed in random order.
+    fn relabelled(graph: &Graph<u32, u32>, seed: u64) -> Graph<u32, u32> {
+        let mut rng = Rng::new(seed);
+        let mut label = (0..graph.vertices() as u32).collect::<Vec<_>>();
+        rng.shuffle(&mut label);
+        let mut edges = graph.edges();
+        rng.shuffle(&mut edges);
+
+        let mut copy = Graph::new(graph.vertices());
+        for (from, to, cost) in edges {
+            copy.add_edge(label[to as usize], label[from as usize], cost);
+        }
+        copy
+    }
+
+    fn cycles(lengths: &[u32]) -> Graph<u32, u32> {
+        let mut graph = Graph::new(lengths.iter().sum::<u32>() as usize);
+        let mut first = 0;
+        for length in lengths {
+            for offset in 0..*length {
+                graph.add_edge(first + offset, first + (offset + 1) % length, 1);
+            }
+            first += length;
+        }
+        graph
+    }
+
+    #[test]
+    fn relabelled_copies_are_isomorphic() {
+        let graphs = generated_graphs()
+            .into_iter()
+            .chain(bundled_graphs().into_iter().map(|(_, graph)| graph));
+        for (seed, graph) in graphs.enumerate() {
+            let copy = relabelled(&graph, seed as u64);
+            for weighted in [false, true] {
+                assert!(graph.is_isomorphic(&copy, weighted));
+                assert_eq!(
+                    graph.weisfeiler_lehman_hash(3, weighted),
+                    copy.weisfeiler_lehman_hash(3, weighted)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn same_degree_sequence_is_not_enough() {
+        // both 2-regular, so colour refinement can't tell them apart
+        let (hexagon, triangles) = (cycles(&[6]), cycles(&[3, 3]));
+        assert_eq!(
+            hexagon.weisfeiler_lehman_hash(3, false),
+            triangles.weisfeiler_lehman_hash(3, false)
+        );
+        assert!(!hexagon.is_isomorphic(&triangles, false));
+        assert!(!triangles.is_isomorphic(&hexagon, false));
+        assert!(hexagon.is_isomorphic(&relabelled(&hexagon, 1), false));
+    }
+
+    #[test]
+    fn weighted_compares_costs() {
+        let mut cheap = cycles(&[5]);
+        let mut expensive = cycles(&[5]);
+        cheap.add_edge(0, 2, 1);
+        expensive.add_edge(0, 2, 2);
+        assert!(cheap.is_isomorphic(&expensive, false));
+        assert!(!cheap.is_isomorphic(&expensive, true));
+    }
+
+    #[test]
+    fn large_graphs_do_not_overflow_the_stack() {
+        let weights = WeightDistribution::UniformInt { low: 1, high: 3 };
+        let graph = erdos_renyi_gnm(50_000, 100_000, &weights, 1);
+        assert!(graph.is_isomorphic(&relabelled(&graph, 2), true));
+    }
+}
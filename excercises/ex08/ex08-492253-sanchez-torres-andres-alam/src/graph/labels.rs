@@ -0,0 +1,195 @@
+//! Optional node names, loaded from a `.names` file next to the graph with
+//! one `<node> <name>` line per named node (1-based ids as in .gph files).
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+use super::{parse_node, AttributedGraph, EdgeAttributes, Graph, GraphError, NodeNo, Numeric};
+
+/// Name of every named node and the reverse lookup.
+#[derive(Clone, Default)]
+pub(super) struct NodeNames {
+    names: HashMap<usize, String>,
+    nodes: HashMap<String, usize>,
+}
+
+impl NodeNames {
+    pub(super) fn name_of(&self, node: usize) -> Option<&str> {
+        self.names.get(&node).map(String::as_str)
+    }
+
+    pub(super) fn node_by_name(&self, name: &str) -> Option<usize> {
+        self.nodes.get(name).copied()
+    }
+
+    /// `false` when another node already has the name.
+    pub(super) fn set(&mut self, node: usize, name: &str) -> bool {
+        match self.nodes.get(name) {
+            Some(named_node) if *named_node != node => return false,
+            _ => {}
+        }
+
+        if let Some(old_name) = self.names.insert(node, name.to_string()) {
+            self.nodes.remove(&old_name);
+        }
+        self.nodes.insert(name.to_string(), node);
+
+        true
+    }
+
+    pub(super) fn read_from_file<T: NodeNo>(
+        &mut self,
+        file_path: &str,
+        vertices: usize,
+    ) -> Result<(), GraphError> {
+        let reader = BufReader::new(File::open(file_path)?);
+
+        for (line_index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line_no = line_index + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            // like .gph values, id and name may be separated by any whitespace
+            let (raw_node, name) = line
+                .split_once(|character: char| character.is_ascii_whitespace())
+                .ok_or(GraphError::InvalidName { line: line_no })?;
+            let node: T = parse_node(Some(raw_node), line_no, vertices)?;
+            let name = name.trim();
+            if !self.set(node.to_index(), name) {
+                return Err(GraphError::DuplicateName {
+                    line: line_no,
+                    name: name.to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Name, or the 1-based id for unnamed nodes.
+    fn label_of(&self, node: usize) -> String {
+        self.name_of(node)
+            .map_or_else(|| (node + 1).to_string(), str::to_string)
+    }
+
+    /// Name, or else a 1-based id.
+    fn node_from_label<T: NodeNo>(&self, label: &str, vertices: usize) -> Option<T> {
+        if let Some(node) = self.node_by_name(label) {
+            return Some(T::from_index(node));
+        }
+
+        let node = label.parse::<usize>().ok()?;
+        (1..=vertices)
+            .contains(&node)
+            .then(|| T::from_index(node - 1))
+    }
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    pub fn node_by_name(&self, name: &str) -> Option<T> {
+        self.names.node_by_name(name).map(T::from_index)
+    }
+
+    pub fn name_of(&self, node: T) -> Option<&str> {
+        self.names.name_of(node.to_index())
+    }
+
+    /// Names `node`, replacing its previous name. `false` (and no change)
+    /// when another node already has the name.
+    pub fn set_node_name(&mut self, node: T, name: &str) -> bool {
+        self.names.set(node.to_index(), name)
+    }
+
+    /// Loads node names from a `.names` file.
+    pub fn try_read_node_names(&mut self, file_path: &str) -> Result<(), GraphError> {
+        self.names.read_from_file::<T>(file_path, self.vertices)
+    }
+
+    /// Resolves a node given by name or by 1-based id, as typed by users.
+    pub fn node_from_label(&self, label: &str) -> Option<T> {
+        self.names.node_from_label(label, self.vertices)
+    }
+
+    /// The node's name, or its 1-based id when it has none.
+    pub fn label_of(&self, node: T) -> String {
+        self.names.label_of(node.to_index())
+    }
+}
+
+impl<T: NodeNo, A: EdgeAttributes> AttributedGraph<T, A> {
+    pub fn node_by_name(&self, name: &str) -> Option<T> {
+        self.names.node_by_name(name).map(T::from_index)
+    }
+
+    pub fn name_of(&self, node: T) -> Option<&str> {
+        self.names.name_of(node.to_index())
+    }
+
+    pub fn set_node_name(&mut self, node: T, name: &str) -> bool {
+        self.names.set(node.to_index(), name)
+    }
+
+    pub fn try_read_node_names(&mut self, file_path: &str) -> Result<(), GraphError> {
+        self.names.read_from_file::<T>(file_path, self.vertices)
+    }
+
+    pub fn node_from_label(&self, label: &str) -> Option<T> {
+        self.names.node_from_label(label, self.vertices)
+    }
+
+    pub fn label_of(&self, node: T) -> String {
+        self.names.label_of(node.to_index())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+
+    /// Reads `contents` through a temporary file, one per test.
+    fn read_names(test: &str, contents: &str) -> Result<NodeNames, GraphError> {
+        let file_path = env::temp_dir().join(format!("{}-{}.names", test, process::id()));
+        fs::write(&file_path, contents).unwrap();
+        let mut names = NodeNames::default();
+        let result = names.read_from_file::<u32>(file_path.to_str().unwrap(), 4);
+        fs::remove_file(&file_path).unwrap();
+        result.map(|_| names)
+    }
+
+    #[test]
+    fn splits_on_the_first_whitespace_run() {
+        let names = read_names(
+            "whitespace",
+            "1 Berlin\n2\tHamburg\n\n3 \t  Frankfurt am Main \r\n",
+        )
+        .unwrap();
+        assert_eq!(names.name_of(0), Some("Berlin"));
+        assert_eq!(names.name_of(1), Some("Hamburg"));
+        assert_eq!(names.name_of(2), Some("Frankfurt am Main"));
+        assert_eq!(names.name_of(3), None);
+        assert_eq!(names.node_by_name("Hamburg"), Some(1));
+    }
+
+    #[test]
+    fn reports_bad_lines() {
+        assert!(matches!(
+            read_names("bad-lines", "1 Berlin\n2\n"),
+            Err(GraphError::InvalidName { line: 2 })
+        ));
+        assert!(matches!(
+            read_names("bad-lines", "1 Berlin\n\n2\tBerlin\n"),
+            Err(GraphError::DuplicateName { line: 3, .. })
+        ));
+        assert!(matches!(
+            read_names("bad-lines", "5 Munich\n"),
+            Err(GraphError::NodeOutOfRange { line: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,109 @@
+//! Minimum weight perfect matching on a complete graph of `nodes` nodes,
+//! shared by Christofides and the Chinese postman.
+use super::{Numeric, TotalOrd};
+
+/// Largest node count solved exactly; the bitmask DP needs 2^nodes entries.
+const EXACT_LIMIT: usize = 20;
+
+/// Pairs up all `nodes` (an even count) minimising the summed `cost`. Exact
+/// up to `EXACT_LIMIT` nodes; above that a greedy matching is improved by
+/// swapping partners between pairs until no swap helps.
+pub fn min_weight_perfect_matching<W: Numeric>(
+    nodes: usize,
+    cost: impl Fn(usize, usize) -> W,
+) -> Vec<(usize, usize)> {
+    assert!(
+        nodes.is_multiple_of(2),
+        "A perfect matching needs an even node count"
+    );
+
+    if nodes <= EXACT_LIMIT {
+        exact_matching(nodes, cost)
+    } else {
+        greedy_matching(nodes, cost)
+    }
+}
+
+fn exact_matching<W: Numeric>(
+    nodes: usize,
+    cost: impl Fn(usize, usize) -> W,
+) -> Vec<(usize, usize)> {
+    let full_mask = (1usize << nodes) - 1;
+    // best[mask]: cheapest matching of the nodes in `mask`, always pairing its lowest node
+    let mut best: Vec<Option<W>> = vec![None; full_mask + 1];
+    let mut partner = vec![0; full_mask + 1];
+    best[0] = Some(W::zero());
+
+    for mask in 1..=full_mask {
+        if mask.count_ones() % 2 == 1 {
+            continue;
+        }
+        let first = mask.trailing_zeros() as usize;
+        for second in first + 1..nodes {
+            if mask & (1 << second) == 0 {
+                continue;
+            }
+            let rest = mask & !(1 << first) & !(1 << second);
+            if let Some(rest_cost) = best[rest] {
+                let total = rest_cost.saturating_add(cost(first, second));
+                if best[mask].is_none_or(|current| TotalOrd(total) < TotalOrd(current)) {
+                    best[mask] = Some(total);
+                    partner[mask] = second;
+                }
+            }
+        }
+    }
+
+    let mut pairs = vec![];
+    let mut mask = full_mask;
+    while mask != 0 {
+        let first = mask.trailing_zeros() as usize;
+        let second = partner[mask];
+        pairs.push((first, second));
+        mask &= !(1 << first) & !(1 << second);
+    }
+
+    pairs
+}
+
+fn greedy_matching<W: Numeric>(
+    nodes: usize,
+    cost: impl Fn(usize, usize) -> W,
+) -> Vec<(usize, usize)> {
+    let mut candidates = (0..nodes)
+        .flat_map(|first| (first + 1..nodes).map(move |second| (first, second)))
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|(first, second)| TotalOrd(cost(*first, *second)));
+
+    let mut matched = vec![false; nodes];
+    let mut pairs = vec![];
+    for (first, second) in candidates {
+        if !matched[first] && !matched[second] {
+            matched[first] = true;
+            matched[second] = true;
+            pairs.push((first, second));
+        }
+    }
+
+    let pair_cost =
+        |a: usize, b: usize, c: usize, d: usize| TotalOrd(cost(a, b).saturating_add(cost(c, d)));
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..pairs.len() {
+            for j in i + 1..pairs.len() {
+                let ((a, b), (c, d)) = (pairs[i], pairs[j]);
+                let current = pair_cost(a, b, c, d);
+                if pair_cost(a, c, b, d) < current {
+                    (pairs[i], pairs[j]) = ((a, c), (b, d));
+                    improved = true;
+                } else if pair_cost(a, d, b, c) < current {
+                    (pairs[i], pairs[j]) = ((a, d), (b, c));
+                    improved = true;
+                }
+            }
+        }
+    }
+
+    pairs
+}
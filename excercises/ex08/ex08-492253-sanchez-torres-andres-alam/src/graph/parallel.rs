@@ -0,0 +1,313 @@
+//! Multi-threaded BFS and single-source shortest paths on rayon, enabled by
+//! the `parallel` feature. Both give the same depths, distances and paths as
+//! their sequential counterparts.
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use rayon::prelude::*;
+
+use super::{dijkstra_parent, Graph, NodeNo, Numeric, PathDistance, ShortestPathTree};
+
+/// Switch to bottom-up once the frontier's edges exceed the unexplored edges
+/// divided by this (Beamer et al.).
+const BOTTOM_UP_ALPHA: usize = 14;
+/// Switch back to top-down once the frontier holds fewer than the nodes
+/// divided by this.
+const TOP_DOWN_BETA: usize = 24;
+
+const UNVISITED: usize = usize::MAX;
+
+impl<T, W> Graph<T, W>
+where
+    T: NodeNo + Send + Sync,
+    W: Numeric + Send + Sync,
+{
+    /// BFS depth of every node from `start`, `None` when unreachable.
+    ///
+    /// Level-synchronous and direction-optimising: small frontiers claim
+    /// their neighbours top-down, large ones let every unvisited node look
+    /// for a parent in the frontier bottom-up.
+    pub fn par_bfs_levels(&self, start: usize) -> Vec<Option<usize>> {
+        let level = (0..self.vertices)
+            .map(|_| AtomicUsize::new(UNVISITED))
+            .collect::<Vec<_>>();
+        level[start].store(0, Ordering::Relaxed);
+
+        let degree = |node: &usize| self.adjacencies[*node].len();
+        let mut unexplored_edges: usize = self.adjacencies.iter().map(Vec::len).sum();
+        let mut frontier = vec![start];
+        let mut depth = 0;
+        let mut bottom_up = false;
+
+        while !frontier.is_empty() {
+            let frontier_edges: usize = frontier.par_iter().map(degree).sum();
+            unexplored_edges = unexplored_edges.saturating_sub(frontier_edges);
+            if !bottom_up && frontier_edges > unexplored_edges / BOTTOM_UP_ALPHA {
+                bottom_up = true;
+            } else if bottom_up && frontier.len() < self.vertices / TOP_DOWN_BETA {
+                bottom_up = false;
+            }
+
+            frontier = if bottom_up {
+                (0..self.vertices)
+                    .into_par_iter()
+                    .filter(|node| {
+                        level[*node].load(Ordering::Relaxed) == UNVISITED
+                            && self.adjacencies[*node].iter().any(|edge| {
+                                level[edge.to.to_index()].load(Ordering::Relaxed) == depth
+                            })
+                    })
+                    .inspect(|node| level[*node].store(depth + 1, Ordering::Relaxed))
+                    .collect()
+            } else {
+                frontier
+                    .par_iter()
+                    .flat_map_iter(|node| &self.adjacencies[*node])
+                    .map(|edge| edge.to.to_index())
+                    .filter(|adj_node| {
+                        level[*adj_node]
+                            .compare_exchange(
+                                UNVISITED,
+                                depth + 1,
+                                Ordering::Relaxed,
+                                Ordering::Relaxed,
+                            )
+                            .is_ok()
+                    })
+                    .collect()
+            };
+            depth += 1;
+        }
+
+        level
+            .into_iter()
+            .map(|level| Some(level.into_inner()).filter(|level| *level != UNVISITED))
+            .collect()
+    }
+
+    /// Parallel counterpart of `bfs_depth`.
+    pub fn par_bfs_depth(&self, start: usize) -> usize {
+        self.par_bfs_levels(start)
+            .into_iter()
+            .flatten()
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Δ-stepping (Meyer and Sanders): nodes are kept in buckets of width
+    /// `delta` by tentative distance, and each bucket is settled in rounds
+    /// that relax the light edges (cost <= `delta`) of all its nodes in
+    /// parallel, followed by one round over its heavy edges. A `delta` around
+    /// the average edge cost is usually a good start; weights must be
+    /// non-negative, as for Dijkstra.
+    ///
+    /// The relaxations of a round are computed in parallel and applied in a
+    /// fixed order, so the result does not depend on the thread count. The
+    /// parents are picked from the final distances the way Dijkstra picks
+    /// them, so the tree is the one `shortest_path_tree` returns.
+    pub fn par_shortest_path_tree(&self, source: T, delta: W) -> ShortestPathTree<T, W> {
+        self.delta_stepping(source, delta, None)
+    }
+
+    /// Parallel counterpart of `shortest_path`, returning the same path.
+    pub fn par_shortest_path(&self, source: T, target: T, delta: W) -> Option<(W, Vec<T>)> {
+        let tree = self.delta_stepping(source, delta, Some(target.to_index()));
+        Some((tree.distance(target)?, tree.path_to(target)?))
+    }
+
+    /// Stops early once the bucket holding `target` has been settled.
+    fn delta_stepping(&self, source: T, delta: W, target: Option<usize>) -> ShortestPathTree<T, W> {
+        assert!(
+            delta > W::zero(),
+            "Can't run delta-stepping with a non-positive delta"
+        );
+        let width = delta.to_f64();
+        let bucket_of = |cost: W| (cost.to_f64() / width) as usize;
+
+        let mut distance = vec![PathDistance::unreached(source); self.vertices];
+        distance[source.to_index()] = PathDistance::start(source);
+        let mut buckets: BTreeMap<usize, Vec<usize>> =
+            BTreeMap::from([(0, vec![source.to_index()])]);
+
+        // (node, path to it over the edge)
+        let relax = |requests: Vec<(usize, PathDistance<T, W>)>,
+                     distance: &mut [PathDistance<T, W>],
+                     buckets: &mut BTreeMap<usize, Vec<usize>>| {
+            for (node, next) in requests {
+                if next.key() < distance[node].key() {
+                    distance[node] = next;
+                    buckets.entry(bucket_of(next.cost)).or_default().push(node);
+                }
+            }
+        };
+
+        let requests = |nodes: &[usize], distance: &[PathDistance<T, W>], light: bool| {
+            nodes
+                .par_iter()
+                .flat_map_iter(|node| {
+                    let reached = distance[*node];
+                    self.adjacencies[*node]
+                        .iter()
+                        .filter(move |edge| (edge.cost <= delta) == light)
+                        .map(move |edge| {
+                            let next = reached.then(T::from_index(*node), edge.cost);
+                            (edge.to.to_index(), next)
+                        })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        while let Some((index, _)) = buckets.first_key_value() {
+            let index = *index;
+            let mut bucket_nodes = vec![];
+
+            while let Some(mut frontier) = buckets.remove(&index) {
+                // entries left behind when a node moved to a lower bucket
+                frontier.retain(|node| bucket_of(distance[*node].cost) == index);
+                frontier.sort_unstable();
+                frontier.dedup();
+                if frontier.is_empty() {
+                    break;
+                }
+                bucket_nodes.extend(&frontier);
+
+                let light = requests(&frontier, &distance, true);
+                relax(light, &mut distance, &mut buckets);
+            }
+
+            bucket_nodes.sort_unstable();
+            bucket_nodes.dedup();
+            let heavy = requests(&bucket_nodes, &distance, false);
+            relax(heavy, &mut distance, &mut buckets);
+
+            // a heavy edge too small to change a float distance can put nodes
+            // back into this bucket
+            let settled = buckets
+                .first_key_value()
+                .is_none_or(|(next, _)| *next > index);
+            if settled
+                && target.is_some_and(|target| {
+                    distance[target].cost != W::max_value()
+                        && bucket_of(distance[target].cost) <= index
+                })
+            {
+                break;
+            }
+        }
+
+        // whichever relaxation won a tie, take the parent Dijkstra would keep
+        let reached = |node: T| {
+            let reached = distance[node.to_index()];
+            (reached.cost != W::max_value()).then_some(reached)
+        };
+        let parents = (0..self.vertices)
+            .into_par_iter()
+            .map(|node| {
+                let node = T::from_index(node);
+                let neighbours = self.adjacencies[node.to_index()]
+                    .iter()
+                    .map(|edge| (edge.to, edge.cost));
+                dijkstra_parent(node, neighbours, reached).unwrap_or(source)
+            })
+            .collect::<Vec<_>>();
+        for (distance, parent) in distance.iter_mut().zip(parents) {
+            distance.source = parent;
+        }
+
+        ShortestPathTree {
+            sources: vec![source],
+            distance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{bfs_levels, bundled_graphs, generated_graphs};
+    use super::*;
+    use std::fmt::Debug;
+
+    fn assert_same_bfs<W: Numeric + Send + Sync>(name: &str, graph: &Graph<u32, W>, start: usize) {
+        let context = format!("{} from {}", name, start);
+        assert_eq!(
+            graph.par_bfs_levels(start),
+            bfs_levels(graph, start),
+            "{}",
+            context
+        );
+        assert_eq!(
+            graph.par_bfs_depth(start),
+            graph.bfs_depth(start),
+            "{}",
+            context
+        );
+    }
+
+    fn assert_same_tree<W: Numeric + Send + Sync + Debug>(
+        name: &str,
+        graph: &Graph<u32, W>,
+        source: u32,
+        delta: W,
+    ) {
+        let context = format!("{} from {} with delta {:?}", name, source, delta);
+        let expected = graph.shortest_path_tree(source);
+        let tree = graph.par_shortest_path_tree(source, delta);
+        for node in 0..graph.vertices as u32 {
+            assert_eq!(tree.distance(node), expected.distance(node), "{}", context);
+            assert_eq!(tree.parent(node), expected.parent(node), "{}", context);
+        }
+    }
+
+    #[test]
+    fn par_bfs_matches_bfs() {
+        for (index, graph) in generated_graphs().iter().enumerate() {
+            for start in [0, 7, 299] {
+                assert_same_bfs(&format!("generated graph {}", index), graph, start);
+            }
+        }
+    }
+
+    #[test]
+    fn par_shortest_path_tree_matches_dijkstra() {
+        for (index, graph) in generated_graphs().iter().enumerate() {
+            for source in [0, 7, 299] {
+                for delta in [1, 2, 5] {
+                    assert_same_tree(&format!("generated graph {}", index), graph, source, delta);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn par_shortest_path_matches_dijkstra() {
+        for graph in generated_graphs() {
+            for (source, target) in [(0, 299), (5, 123), (42, 42), (299, 1)] {
+                for delta in [1, 3] {
+                    assert_eq!(
+                        graph.par_shortest_path(source, target, delta),
+                        graph.shortest_path(source, target)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bundled_graphs_match_sequential() {
+        for (name, graph) in bundled_graphs::<u32>() {
+            for source in 0..graph.vertices as u32 {
+                assert_same_bfs(&name, &graph, source as usize);
+                for delta in [1, 4, 16] {
+                    assert_same_tree(&name, &graph, source, delta);
+                }
+            }
+        }
+        for (name, graph) in bundled_graphs::<f64>() {
+            for source in 0..graph.vertices as u32 {
+                assert_same_tree(&name, &graph, source, 4.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! How far edge costs may move before the minimum spanning tree changes, and
+//! the cheapest spanning tree that differs from it.
+use std::fmt;
+
+use super::{tree::Tree, Graph, NodeNo, Numeric, TotalOrd};
+
+/// Spanning tree (forest on disconnected graphs) given by its edges.
+pub struct SpanningTree<T: NodeNo, W: Numeric> {
+    pub edges: Vec<(T, T, W)>,
+    pub cost: W,
+}
+
+pub struct EdgeSensitivity<T: NodeNo, W: Numeric> {
+    pub from: T,
+    pub to: T,
+    pub cost: W,
+    pub in_tree: bool,
+    /// For tree edges the largest increase, for the other edges the largest
+    /// decrease, that keeps the minimum spanning tree optimal (ties allowed).
+    /// `None` when unbounded: bridges and self-loops.
+    pub margin: Option<W>,
+}
+
+/// Sensitivity of every edge, in the order of `Graph::edges`.
+pub struct SensitivityReport<T: NodeNo, W: Numeric> {
+    pub mst_cost: W,
+    pub edges: Vec<EdgeSensitivity<T, W>>,
+}
+
+impl<T: NodeNo, W: Numeric> fmt::Display for SensitivityReport<T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "MST cost: {}", self.mst_cost)?;
+        writeln!(
+            f,
+            "{:>8} {:>8} {:>12} {:>5} {:>12}",
+            "from", "to", "cost", "tree", "margin"
+        )?;
+        for edge in &self.edges {
+            let (in_tree, margin) = match (edge.in_tree, edge.margin) {
+                (true, Some(margin)) => ("yes", format!("+{}", margin)),
+                (false, Some(margin)) => ("no", format!("-{}", margin)),
+                (true, None) => ("yes", "unbounded".to_string()),
+                (false, None) => ("no", "unbounded".to_string()),
+            };
+            writeln!(
+                f,
+                "{:>8} {:>8} {:>12} {:>5} {:>12}",
+                edge.from + T::one(),
+                edge.to + T::one(),
+                edge.cost,
+                in_tree,
+                margin
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Edges of `Graph::edges` split by whether they are in the tree of
+/// `Graph::minimum_spanning_tree_edges`.
+struct MstSplit<T: NodeNo, W: Numeric> {
+    edges: Vec<(T, T, W)>,
+    in_tree: Vec<bool>,
+    tree: Tree<T, W>,
+    // id of the tree edge to each node's parent
+    parent_edge: Vec<Option<usize>>,
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    fn split_by_mst(&self) -> MstSplit<T, W> {
+        let edges = self.edges();
+        let mut in_tree = vec![false; edges.len()];
+        let mut tree_edges = vec![];
+        for id in self.minimum_spanning_tree_ids(&edges) {
+            in_tree[id] = true;
+            tree_edges.push(edges[id]);
+        }
+        let tree = Tree::from_edges(self.vertices, &tree_edges);
+
+        let mut parent_edge = vec![None; self.vertices];
+        for id in (0..edges.len()).filter(|id| in_tree[*id]) {
+            let (from, to, _) = edges[id];
+            let child = if tree.parent(from) == Some(to) {
+                from
+            } else {
+                to
+            };
+            parent_edge[child.to_index()] = Some(id);
+        }
+
+        MstSplit {
+            edges,
+            in_tree,
+            tree,
+            parent_edge,
+        }
+    }
+
+    /// Cheapest spanning tree other than the one of
+    /// `minimum_spanning_tree_edges`, found by swapping a single non-tree edge
+    /// for the most expensive tree edge on the cycle it closes. Its cost may
+    /// equal the minimum when there are ties. `None` for forests, which have
+    /// no other spanning tree.
+    pub fn second_best_mst(&self) -> Option<SpanningTree<T, W>> {
+        let MstSplit {
+            edges,
+            in_tree,
+            tree,
+            parent_edge,
+        } = self.split_by_mst();
+
+        let (added, (removed_child, _, _)) = (0..edges.len())
+            .filter(|id| !in_tree[*id])
+            .filter_map(|id| {
+                let (from, to, _) = edges[id];
+                Some((id, tree.max_edge_on_path(from, to)?))
+            })
+            .min_by_key(|(id, (_, _, removed_cost))| {
+                // the MST cost is the same for every swap, so compare the difference
+                TotalOrd(edges[*id].2 - *removed_cost)
+            })?;
+
+        let removed = parent_edge[removed_child.to_index()].unwrap();
+
+        let mut cost = W::zero();
+        let edges = (0..edges.len())
+            .filter(|id| (in_tree[*id] && *id != removed) || *id == added)
+            .map(|id| {
+                cost += edges[id].2;
+                edges[id]
+            })
+            .collect();
+
+        Some(SpanningTree { edges, cost })
+    }
+
+    /// Per-edge cost margins of the minimum spanning tree. A non-tree edge
+    /// may drop to the most expensive tree edge on the cycle it closes; a
+    /// tree edge may rise to the cheapest non-tree edge whose cycle covers
+    /// it. O(|E| log |V|) after Kruskal.
+    pub fn mst_sensitivity(&self) -> SensitivityReport<T, W> {
+        let MstSplit {
+            edges,
+            in_tree,
+            tree,
+            parent_edge,
+        } = self.split_by_mst();
+
+        let mut margin: Vec<Option<W>> = vec![None; edges.len()];
+        let mut mst_cost = W::zero();
+        for id in (0..edges.len()).filter(|id| in_tree[*id]) {
+            mst_cost += edges[id].2;
+        }
+
+        let mut non_tree = (0..edges.len())
+            .filter(|id| !in_tree[*id] && edges[*id].0 != edges[*id].1)
+            .collect::<Vec<_>>();
+        non_tree.sort_by_key(|id| TotalOrd(edges[*id].2));
+
+        // nearest ancestor (or self) whose parent edge has no margin yet
+        let mut uncovered = (0..self.vertices).collect::<Vec<_>>();
+        fn find(uncovered: &mut [usize], node: usize) -> usize {
+            let mut root = node;
+            while uncovered[root] != root {
+                root = uncovered[root];
+            }
+            let mut current_node = node;
+            while current_node != root {
+                current_node = std::mem::replace(&mut uncovered[current_node], root);
+            }
+            root
+        }
+
+        // cheapest non-tree edges first, so each tree edge keeps the first cover
+        for id in non_tree {
+            let (from, to, cost) = edges[id];
+            let Some((_, _, heaviest)) = tree.max_edge_on_path(from, to) else {
+                continue;
+            };
+            margin[id] = Some(cost - heaviest);
+
+            let lca_depth = tree.depth(tree.lca(from, to).unwrap());
+            for end in [from, to] {
+                let mut node = find(&mut uncovered, end.to_index());
+                while tree.depth(T::from_index(node)) > lca_depth {
+                    let tree_id = parent_edge[node].unwrap();
+                    margin[tree_id] = Some(cost - edges[tree_id].2);
+
+                    let parent = tree.parent(T::from_index(node)).unwrap().to_index();
+                    uncovered[node] = parent;
+                    node = find(&mut uncovered, parent);
+                }
+            }
+        }
+
+        let edges = edges
+            .into_iter()
+            .zip(in_tree)
+            .zip(margin)
+            .map(|(((from, to, cost), in_tree), margin)| EdgeSensitivity {
+                from,
+                to,
+                cost,
+                in_tree,
+                margin,
+            })
+            .collect();
+
+        SensitivityReport { mst_cost, edges }
+    }
+}
@@ -0,0 +1,514 @@
+//! Spectral graph tools: sparse adjacency and Laplacian matrices, a Lanczos
+//! eigen-solver for their smallest eigenpairs, algebraic connectivity and
+//! Fiedler vector bisection. Edge costs are read as connection strengths and
+//! parallel edges add up. Self-loops cancel out of `D - A`, but not out of
+//! the normalized Laplacian: they count towards the degree, and node `i`'s
+//! diagonal becomes `1 - A_ii / d_i`.
+use super::{rng::Rng, Graph, NodeNo, Numeric};
+
+/// Symmetric matrices here are small enough to scan rows, but big enough
+/// that dense storage is out of the question: compressed sparse rows.
+#[derive(Clone, Debug)]
+pub struct SparseMatrix {
+    size: usize,
+    row_start: Vec<usize>,
+    columns: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl SparseMatrix {
+    /// Builds a `size` x `size` matrix from `(row, column, value)` entries,
+    /// summing duplicates.
+    pub fn from_entries(size: usize, mut entries: Vec<(usize, usize, f64)>) -> Self {
+        entries.sort_by_key(|(row, column, _)| (*row, *column));
+
+        let mut row_start = vec![0; size + 1];
+        let mut columns: Vec<usize> = vec![];
+        let mut values: Vec<f64> = vec![];
+        let mut last = None;
+        for (row, column, value) in entries {
+            if last == Some((row, column)) {
+                *values.last_mut().unwrap() += value;
+                continue;
+            }
+            last = Some((row, column));
+            columns.push(column);
+            values.push(value);
+            row_start[row + 1] = columns.len();
+        }
+        // rows without entries start where the previous one ended
+        for row in 1..=size {
+            row_start[row] = row_start[row].max(row_start[row - 1]);
+        }
+
+        SparseMatrix {
+            size,
+            row_start,
+            columns,
+            values,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Number of stored entries.
+    pub fn non_zeros(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> f64 {
+        let range = self.row_start[row]..self.row_start[row + 1];
+        match self.columns[range.clone()].binary_search(&column) {
+            Ok(offset) => self.values[range.start + offset],
+            Err(_) => 0.0,
+        }
+    }
+
+    /// `(column, value)` entries of a row.
+    pub fn row(&self, row: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        let range = self.row_start[row]..self.row_start[row + 1];
+        self.columns[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.values[range].iter().copied())
+    }
+
+    pub fn multiply(&self, vector: &[f64]) -> Vec<f64> {
+        (0..self.size)
+            .map(|row| {
+                self.row(row)
+                    .map(|(column, value)| value * vector[column])
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// The `count` smallest eigenvalues with unit eigenvectors, ascending,
+    /// for a symmetric matrix.
+    ///
+    /// Lanczos with full reorthogonalisation: the Krylov basis grows until
+    /// the residual of every wanted Ritz pair is below `tolerance` (relative
+    /// to the matrix scale) or the basis spans the whole space. When the
+    /// Krylov space closes early (disconnected graphs, repeated eigenvalues)
+    /// it restarts from a fresh orthogonal vector, so multiplicities are
+    /// found too. Memory is one vector per Lanczos step.
+    pub fn smallest_eigenpairs(&self, count: usize, tolerance: f64) -> Vec<(f64, Vec<f64>)> {
+        let size = self.size;
+        let count = count.min(size);
+        if count == 0 {
+            return vec![];
+        }
+
+        // Gershgorin bound on the spectral radius
+        let scale = (0..size)
+            .map(|row| self.row(row).map(|(_, value)| value.abs()).sum::<f64>())
+            .fold(0.0, f64::max)
+            .max(f64::MIN_POSITIVE);
+
+        let mut rng = Rng::new(0x5EED);
+        let mut basis: Vec<Vec<f64>> = vec![];
+        let mut alpha = vec![];
+        let mut beta: Vec<f64> = vec![];
+        let mut vector = random_orthogonal(size, &basis, &mut rng);
+
+        loop {
+            let mut next = self.multiply(&vector);
+            let diagonal = dot(&next, &vector);
+            basis.push(vector);
+            alpha.push(diagonal);
+
+            // two passes of Gram-Schmidt keep the basis orthogonal
+            for _ in 0..2 {
+                for basis_vector in &basis {
+                    let projection = dot(&next, basis_vector);
+                    axpy(&mut next, -projection, basis_vector);
+                }
+            }
+            let norm = dot(&next, &next).sqrt();
+
+            let steps = basis.len();
+            let closed = norm <= 1e-10 * scale;
+            // solving the tridiagonal problem is O(steps^3), so only check
+            // every few steps; a closed Krylov space says nothing about the
+            // eigenvalues outside it
+            let check = steps == size || (steps >= count && steps.is_multiple_of(8) && !closed);
+            if check {
+                let (values, vectors) = tridiagonal_eigen(&alpha, &beta);
+                let converged = (0..count).all(|index| {
+                    (norm * vectors[steps - 1][index]).abs()
+                        <= tolerance * scale.max(values[index].abs())
+                });
+
+                if steps == size || converged {
+                    return (0..count)
+                        .map(|index| {
+                            let mut eigenvector = vec![0.0; size];
+                            for (step, basis_vector) in basis.iter().enumerate() {
+                                axpy(&mut eigenvector, vectors[step][index], basis_vector);
+                            }
+                            normalize_sign(&mut eigenvector);
+                            (values[index], eigenvector)
+                        })
+                        .collect();
+                }
+            }
+
+            if closed {
+                // invariant subspace found, continue in its complement
+                beta.push(0.0);
+                vector = random_orthogonal(size, &basis, &mut rng);
+            } else {
+                beta.push(norm);
+                vector = next.into_iter().map(|value| value / norm).collect();
+            }
+        }
+    }
+}
+
+fn dot(first: &[f64], second: &[f64]) -> f64 {
+    first.iter().zip(second).map(|(a, b)| a * b).sum()
+}
+
+/// `target += factor * vector`
+fn axpy(target: &mut [f64], factor: f64, vector: &[f64]) {
+    for (value, added) in target.iter_mut().zip(vector) {
+        *value += factor * added;
+    }
+}
+
+/// Unit vector orthogonal to the (orthonormal) `basis`.
+fn random_orthogonal(size: usize, basis: &[Vec<f64>], rng: &mut Rng) -> Vec<f64> {
+    loop {
+        let mut vector = (0..size).map(|_| rng.next_f64() - 0.5).collect::<Vec<_>>();
+        for _ in 0..2 {
+            for basis_vector in basis {
+                let projection = dot(&vector, basis_vector);
+                axpy(&mut vector, -projection, basis_vector);
+            }
+        }
+        let norm = dot(&vector, &vector).sqrt();
+        if norm > 1e-8 {
+            return vector.into_iter().map(|value| value / norm).collect();
+        }
+    }
+}
+
+/// Flips the vector so its largest entry (by magnitude, first on ties) is
+/// positive, making results reproducible.
+fn normalize_sign(vector: &mut [f64]) {
+    let largest = vector.iter().copied().fold(0.0, |best: f64, value| {
+        if value.abs() > best.abs() {
+            value
+        } else {
+            best
+        }
+    });
+    if largest < 0.0 {
+        for value in vector.iter_mut() {
+            *value = -*value;
+        }
+    }
+}
+
+/// Eigenvalues (ascending) and eigenvectors of the symmetric tridiagonal
+/// matrix with diagonal `diagonal` and off-diagonal `off_diagonal`, by the
+/// implicit QL algorithm (tql2 of EISPACK). `vectors[row][index]` is entry
+/// `row` of eigenvector `index`.
+fn tridiagonal_eigen(diagonal: &[f64], off_diagonal: &[f64]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let size = diagonal.len();
+    let mut d = diagonal.to_vec();
+    let mut e = off_diagonal[..size - 1].to_vec();
+    e.push(0.0);
+    let mut v = (0..size)
+        .map(|row| {
+            (0..size)
+                .map(|column| f64::from(u8::from(row == column)))
+                .collect()
+        })
+        .collect::<Vec<Vec<f64>>>();
+
+    let mut shift = 0.0;
+    let mut largest: f64 = 0.0;
+    for l in 0..size {
+        largest = largest.max(d[l].abs() + e[l].abs());
+        let mut m = l;
+        while e[m].abs() > f64::EPSILON * largest {
+            m += 1;
+        }
+
+        if m > l {
+            loop {
+                let g = d[l];
+                let mut p = (d[l + 1] - g) / (2.0 * e[l]);
+                let mut r = p.hypot(1.0);
+                if p < 0.0 {
+                    r = -r;
+                }
+                d[l] = e[l] / (p + r);
+                d[l + 1] = e[l] * (p + r);
+                let next_diagonal = d[l + 1];
+                let mut h = g - d[l];
+                for value in d.iter_mut().skip(l + 2) {
+                    *value -= h;
+                }
+                shift += h;
+
+                p = d[m];
+                let (mut c, mut c2, mut c3) = (1.0, 1.0, 1.0);
+                let next_off_diagonal = e[l + 1];
+                let (mut s, mut s2) = (0.0, 0.0);
+                for i in (l..m).rev() {
+                    c3 = c2;
+                    c2 = c;
+                    s2 = s;
+                    let g = c * e[i];
+                    h = c * p;
+                    r = p.hypot(e[i]);
+                    e[i + 1] = s * r;
+                    s = e[i] / r;
+                    c = p / r;
+                    p = c * d[i] - s * g;
+                    d[i + 1] = h + s * (c * g + s * d[i]);
+
+                    for row in v.iter_mut() {
+                        h = row[i + 1];
+                        row[i + 1] = s * row[i] + c * h;
+                        row[i] = c * row[i] - s * h;
+                    }
+                }
+                p = -s * s2 * c3 * next_off_diagonal * e[l] / next_diagonal;
+                e[l] = s * p;
+                d[l] = c * p;
+
+                if e[l].abs() <= f64::EPSILON * largest {
+                    break;
+                }
+            }
+        }
+        d[l] += shift;
+        e[l] = 0.0;
+    }
+
+    let mut order = (0..size).collect::<Vec<_>>();
+    order.sort_by(|first, second| d[*first].total_cmp(&d[*second]));
+    let values = order.iter().map(|index| d[*index]).collect();
+    let vectors = v
+        .iter()
+        .map(|row| order.iter().map(|index| row[*index]).collect())
+        .collect();
+
+    (values, vectors)
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    /// Symmetric weighted adjacency matrix; a self-loop adds its cost once to
+    /// the diagonal.
+    pub fn adjacency_matrix(&self) -> SparseMatrix {
+        let mut entries = vec![];
+        for (from, to, cost) in self.edges() {
+            let (from, to, cost) = (from.to_index(), to.to_index(), cost.to_f64());
+            entries.push((from, to, cost));
+            if from != to {
+                entries.push((to, from, cost));
+            }
+        }
+
+        SparseMatrix::from_entries(self.vertices, entries)
+    }
+
+    /// `L = D - A`, or `I - D^-1/2 A D^-1/2` when `normalized` (with zero
+    /// rows for isolated nodes), where `D` holds the weighted degrees,
+    /// self-loops included.
+    pub fn laplacian(&self, normalized: bool) -> SparseMatrix {
+        let adjacency = self.adjacency_matrix();
+        let degree = (0..self.vertices)
+            .map(|node| adjacency.row(node).map(|(_, value)| value).sum::<f64>())
+            .collect::<Vec<_>>();
+        let scale = |node: usize| {
+            if normalized && degree[node] > 0.0 {
+                1.0 / degree[node].sqrt()
+            } else if normalized {
+                0.0
+            } else {
+                1.0
+            }
+        };
+
+        let mut entries = vec![];
+        for (node, node_degree) in degree.iter().enumerate() {
+            entries.push((node, node, node_degree * scale(node) * scale(node)));
+            for (column, value) in adjacency.row(node) {
+                entries.push((node, column, -value * scale(node) * scale(column)));
+            }
+        }
+
+        SparseMatrix::from_entries(self.vertices, entries)
+    }
+
+    /// Second smallest Laplacian eigenvalue: zero (up to rounding) when the
+    /// graph is disconnected, and larger the harder the graph is to cut.
+    pub fn algebraic_connectivity(&self, normalized: bool) -> f64 {
+        match self.fiedler_pair(normalized) {
+            Some((value, _)) => value.max(0.0),
+            None => 0.0,
+        }
+    }
+
+    /// Eigenvector of the algebraic connectivity. `None` for fewer than two
+    /// nodes; arbitrary within the zero eigenspace for disconnected graphs.
+    pub fn fiedler_vector(&self, normalized: bool) -> Option<Vec<f64>> {
+        self.fiedler_pair(normalized).map(|(_, vector)| vector)
+    }
+
+    fn fiedler_pair(&self, normalized: bool) -> Option<(f64, Vec<f64>)> {
+        if self.vertices < 2 {
+            return None;
+        }
+        self.laplacian(normalized)
+            .smallest_eigenpairs(2, 1e-10)
+            .pop()
+    }
+
+    /// Splits the nodes into two halves (the first one larger for an odd
+    /// count) by the median of the Fiedler vector, which approximately
+    /// minimises the cut between equally sized parts.
+    pub fn spectral_bisection(&self, normalized: bool) -> (Vec<T>, Vec<T>) {
+        let Some(fiedler) = self.fiedler_vector(normalized) else {
+            return ((0..self.vertices).map(T::from_index).collect(), vec![]);
+        };
+
+        let mut order = (0..self.vertices).collect::<Vec<_>>();
+        order.sort_by(|first, second| fiedler[*first].total_cmp(&fiedler[*second]));
+        let mut second = order.split_off(self.vertices.div_ceil(2));
+        let mut first = order;
+        first.sort_unstable();
+        second.sort_unstable();
+
+        (
+            first.into_iter().map(T::from_index).collect(),
+            second.into_iter().map(T::from_index).collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            generate::{erdos_renyi_gnp, WeightDistribution},
+            testing::{barbell, generated_graphs},
+        },
+        *,
+    };
+
+    /// All eigenvalues, ascending, of a small symmetric matrix by cyclic
+    /// Jacobi rotations on its dense copy.
+    fn dense_eigenvalues(matrix: &SparseMatrix) -> Vec<f64> {
+        let size = matrix.size();
+        let mut dense = (0..size)
+            .map(|row| (0..size).map(|column| matrix.get(row, column)).collect())
+            .collect::<Vec<Vec<f64>>>();
+
+        for _ in 0..100 {
+            for p in 0..size {
+                for q in p + 1..size {
+                    if dense[p][q] == 0.0 {
+                        continue;
+                    }
+                    let theta = (dense[q][q] - dense[p][p]) / (2.0 * dense[p][q]);
+                    let t = theta.signum() / (theta.abs() + theta.hypot(1.0));
+                    let c = 1.0 / t.hypot(1.0);
+                    let s = t * c;
+                    for row in dense.iter_mut() {
+                        let (row_p, row_q) = (row[p], row[q]);
+                        row[p] = c * row_p - s * row_q;
+                        row[q] = s * row_p + c * row_q;
+                    }
+                    let (head, tail) = dense.split_at_mut(q);
+                    for (p_value, q_value) in head[p].iter_mut().zip(tail[0].iter_mut()) {
+                        (*p_value, *q_value) =
+                            (c * *p_value - s * *q_value, s * *p_value + c * *q_value);
+                    }
+                }
+            }
+        }
+
+        let mut values = (0..size)
+            .map(|index| dense[index][index])
+            .collect::<Vec<_>>();
+        values.sort_by(f64::total_cmp);
+        values
+    }
+
+    fn assert_eigenpairs(matrix: &SparseMatrix, count: usize) {
+        let expected = dense_eigenvalues(matrix);
+        let pairs = matrix.smallest_eigenpairs(count, 1e-10);
+        assert_eq!(pairs.len(), count.min(matrix.size()));
+
+        for (index, (value, vector)) in pairs.iter().enumerate() {
+            assert!(
+                (value - expected[index]).abs() < 1e-8,
+                "eigenvalue {index}: {value} vs {}",
+                expected[index]
+            );
+            assert!((dot(vector, vector) - 1.0).abs() < 1e-8);
+            let residual = matrix
+                .multiply(vector)
+                .iter()
+                .zip(vector)
+                .map(|(product, entry)| (product - value * entry).powi(2))
+                .sum::<f64>();
+            assert!(residual.sqrt() < 1e-6, "residual of eigenpair {index}");
+        }
+    }
+
+    #[test]
+    fn lanczos_matches_dense_solve() {
+        let weights = WeightDistribution::Uniform {
+            low: 0.5,
+            high: 3.0,
+        };
+        let mut graphs = (0..6)
+            .map(|seed| erdos_renyi_gnp::<u32, f64>(12 + seed as usize, 0.3, &weights, seed))
+            .collect::<Vec<_>>();
+        // disconnected, so zero is a repeated eigenvalue
+        graphs.push(erdos_renyi_gnp(20, 0.05, &weights, 9));
+        graphs.push(barbell(4));
+
+        for graph in graphs {
+            for normalized in [false, true] {
+                let laplacian = graph.laplacian(normalized);
+                assert_eigenpairs(&laplacian, 4);
+                assert_eigenpairs(&laplacian, laplacian.size());
+            }
+        }
+    }
+
+    #[test]
+    fn fiedler_vector_splits_a_barbell() {
+        let graph = barbell::<f64>(6);
+        for normalized in [false, true] {
+            let fiedler = graph.fiedler_vector(normalized).unwrap();
+            let sign = fiedler[0].signum();
+            assert!(fiedler[..6].iter().all(|value| value.signum() == sign));
+            assert!(fiedler[6..].iter().all(|value| value.signum() == -sign));
+            assert_eq!(
+                graph.spectral_bisection(normalized),
+                (vec![0, 1, 2, 3, 4, 5], vec![6, 7, 8, 9, 10, 11])
+            );
+            assert!(graph.algebraic_connectivity(normalized) > 0.0);
+        }
+    }
+
+    #[test]
+    fn laplacian_rows_sum_to_zero() {
+        for graph in generated_graphs() {
+            let laplacian = graph.laplacian(false);
+            for row in 0..laplacian.size() {
+                assert_eq!(laplacian.row(row).map(|(_, value)| value).sum::<f64>(), 0.0);
+            }
+        }
+    }
+}
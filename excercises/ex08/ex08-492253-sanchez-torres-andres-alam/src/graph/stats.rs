@@ -0,0 +1,209 @@
+//! Quick profile of a graph: size, header consistency, self-loops and
+//! parallel edges, degrees, weights, components and structural flags.
+use std::{collections::VecDeque, fmt};
+
+use super::{Graph, NodeNo, Numeric};
+
+pub struct DegreeSummary {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    pub median: f64,
+    /// Nodes without any edge.
+    pub isolated: usize,
+}
+
+pub struct WeightSummary<W: Numeric> {
+    pub min: W,
+    pub max: W,
+    pub mean: f64,
+}
+
+pub struct GraphStats<W: Numeric> {
+    pub vertices: usize,
+    pub edges: usize,
+    /// Edge count of the .gph header, `None` for graphs not read from text.
+    pub declared_edges: Option<usize>,
+    pub self_loops: usize,
+    /// Edges joining a pair of nodes (or a node to itself) that an earlier
+    /// edge already joins.
+    pub multi_edges: usize,
+    /// `None` for the empty graph.
+    pub degree: Option<DegreeSummary>,
+    /// `None` without edges.
+    pub weight: Option<WeightSummary<W>>,
+    /// Largest first.
+    pub component_sizes: Vec<usize>,
+    /// Distinct adjacent pairs over all possible pairs, ignoring self-loops.
+    pub density: f64,
+    pub is_tree: bool,
+    pub is_forest: bool,
+    pub is_bipartite: bool,
+}
+
+impl<W: Numeric> GraphStats<W> {
+    pub fn components(&self) -> usize {
+        self.component_sizes.len()
+    }
+
+    /// Whether the header's edge count matches the edge lines, `None` when
+    /// there was no header.
+    pub fn header_matches(&self) -> Option<bool> {
+        self.declared_edges.map(|declared| declared == self.edges)
+    }
+}
+
+impl<W: Numeric> fmt::Display for GraphStats<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+
+        writeln!(f, "vertices:     {}", self.vertices)?;
+        match self.declared_edges {
+            Some(declared) if declared != self.edges => {
+                writeln!(f, "edges:        {} (header says {})", self.edges, declared)?
+            }
+            _ => writeln!(f, "edges:        {}", self.edges)?,
+        }
+        writeln!(f, "self-loops:   {}", self.self_loops)?;
+        writeln!(f, "multi-edges:  {}", self.multi_edges)?;
+        match &self.degree {
+            Some(degree) => writeln!(
+                f,
+                "degree:       min {} max {} mean {:.3} median {} isolated {}",
+                degree.min, degree.max, degree.mean, degree.median, degree.isolated
+            )?,
+            None => writeln!(f, "degree:       -")?,
+        }
+        match &self.weight {
+            Some(weight) => writeln!(
+                f,
+                "weight:       min {} max {} mean {:.3}",
+                weight.min, weight.max, weight.mean
+            )?,
+            None => writeln!(f, "weight:       -")?,
+        }
+        let largest = self.component_sizes.iter().take(10);
+        let sizes = largest.map(usize::to_string).collect::<Vec<_>>();
+        let more = if self.components() > sizes.len() {
+            " ..."
+        } else {
+            ""
+        };
+        writeln!(
+            f,
+            "components:   {} (sizes {}{})",
+            self.components(),
+            sizes.join(" "),
+            more
+        )?;
+        writeln!(f, "density:      {:.6}", self.density)?;
+        writeln!(
+            f,
+            "tree: {}  forest: {}  bipartite: {}",
+            yes_no(self.is_tree),
+            yes_no(self.is_forest),
+            yes_no(self.is_bipartite)
+        )
+    }
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    pub fn stats(&self) -> GraphStats<W> {
+        let edges = self.edges();
+
+        let mut pairs = edges
+            .iter()
+            .map(|(from, to, _)| (from.to_index(), to.to_index()))
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        let self_loops = pairs.iter().filter(|(from, to)| from == to).count();
+        let multi_edges = pairs.windows(2).filter(|pair| pair[0] == pair[1]).count();
+        pairs.dedup();
+        let distinct_pairs = pairs.iter().filter(|(from, to)| from != to).count();
+        let density = if self.vertices > 1 {
+            distinct_pairs as f64 / (self.vertices * (self.vertices - 1) / 2) as f64
+        } else {
+            0.0
+        };
+
+        let mut degrees = self.adjacencies.iter().map(Vec::len).collect::<Vec<_>>();
+        degrees.sort_unstable();
+        let degree = (!degrees.is_empty()).then(|| {
+            let middle = degrees.len() / 2;
+            DegreeSummary {
+                min: degrees[0],
+                max: degrees[degrees.len() - 1],
+                mean: degrees.iter().sum::<usize>() as f64 / degrees.len() as f64,
+                median: if degrees.len() % 2 == 1 {
+                    degrees[middle] as f64
+                } else {
+                    (degrees[middle - 1] + degrees[middle]) as f64 / 2.0
+                },
+                isolated: degrees.iter().take_while(|degree| **degree == 0).count(),
+            }
+        });
+
+        let weight = (!edges.is_empty()).then(|| {
+            let costs = edges.iter().map(|(_, _, cost)| *cost);
+            let min = costs
+                .clone()
+                .reduce(|min, cost| if cost < min { cost } else { min });
+            let max = costs
+                .clone()
+                .reduce(|max, cost| if cost > max { cost } else { max });
+            WeightSummary {
+                min: min.unwrap(),
+                max: max.unwrap(),
+                mean: costs.map(|cost| cost.to_f64()).sum::<f64>() / edges.len() as f64,
+            }
+        });
+
+        // BFS 2-colouring: finds the components and any odd cycle
+        let mut side: Vec<Option<bool>> = vec![None; self.vertices];
+        let mut component_sizes = vec![];
+        let mut is_bipartite = self_loops == 0;
+        for root in 0..self.vertices {
+            if side[root].is_some() {
+                continue;
+            }
+            side[root] = Some(false);
+            let mut size = 0;
+            let mut bfs_queue = VecDeque::from([root]);
+            while let Some(current_node) = bfs_queue.pop_front() {
+                size += 1;
+                let current_side = side[current_node].unwrap();
+                for edge in &self.adjacencies[current_node] {
+                    let adj_node = edge.to.to_index();
+                    match side[adj_node] {
+                        None => {
+                            side[adj_node] = Some(!current_side);
+                            bfs_queue.push_back(adj_node);
+                        }
+                        Some(adj_side) if adj_side == current_side => is_bipartite = false,
+                        Some(_) => {}
+                    }
+                }
+            }
+            component_sizes.push(size);
+        }
+        component_sizes.sort_unstable_by(|first, second| second.cmp(first));
+
+        // every edge beyond a spanning forest closes a cycle
+        let is_forest = edges.len() + component_sizes.len() == self.vertices;
+
+        GraphStats {
+            vertices: self.vertices,
+            edges: edges.len(),
+            declared_edges: self.declared_edges,
+            self_loops,
+            multi_edges,
+            degree,
+            weight,
+            is_tree: is_forest && component_sizes.len() == 1,
+            component_sizes,
+            density,
+            is_forest,
+            is_bipartite,
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Steiner trees: the cheapest trees connecting a subset of terminal nodes,
+//! optionally through other nodes.
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use super::{Graph, NodeNo, Numeric, TotalOrd};
+
+pub struct SteinerTree<T: NodeNo, W: Numeric> {
+    pub edges: Vec<(T, T, W)>,
+    pub cost: W,
+}
+
+/// How `best[mask][node]` of Dreyfus–Wagner was reached.
+#[derive(Clone, Copy)]
+enum SteinerStep {
+    Unreached,
+    /// `node` is the only terminal in the mask
+    Terminal,
+    /// union of the trees for `submask` and `mask ^ submask`, both at `node`
+    Merge(usize),
+    /// tree at `previous` plus the edge `previous -> node`
+    Extend(usize),
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    fn unique_terminals(terminals: &[T]) -> Vec<usize> {
+        let mut unique = terminals
+            .iter()
+            .map(|node| node.to_index())
+            .collect::<Vec<_>>();
+        unique.sort_unstable();
+        unique.dedup();
+        unique
+    }
+
+    fn steiner_from_edges(&self, edges: Vec<(usize, usize)>) -> SteinerTree<T, W> {
+        let mut cost = W::zero();
+        let edges = edges
+            .into_iter()
+            .map(|(from, to)| {
+                let edge_cost = self.cheapest_edge(from, to).unwrap();
+                cost += edge_cost;
+                (T::from_index(from), T::from_index(to), edge_cost)
+            })
+            .collect();
+
+        SteinerTree { edges, cost }
+    }
+
+    /// Shortest path heuristic of Kou, Markowsky and Berman: a minimum
+    /// spanning tree (Kruskal) of the terminals' shortest path distances,
+    /// expanded into graph paths, re-spanned and stripped of non-terminal
+    /// leaves. Costs at most `2 - 2 / |terminals|` times the optimum.
+    /// `None` when the terminals are not connected.
+    pub fn steiner_tree(&self, terminals: &[T]) -> Option<SteinerTree<T, W>> {
+        let terminals = Self::unique_terminals(terminals);
+
+        let trees = terminals
+            .iter()
+            .map(|terminal| self.shortest_path_tree(T::from_index(*terminal)))
+            .collect::<Vec<_>>();
+
+        let mut terminal_graph: Graph<T, W> = Graph::new(terminals.len());
+        for (first, tree) in trees.iter().enumerate() {
+            for (second, terminal) in terminals.iter().enumerate().skip(first + 1) {
+                let distance = tree.distance(T::from_index(*terminal))?;
+                terminal_graph.add_edge(T::from_index(first), T::from_index(second), distance);
+            }
+        }
+
+        // expand the terminal MST into graph edges, then span them again to
+        // drop cycles formed by overlapping paths
+        let mut expanded: Graph<T, W> = Graph::new(self.vertices);
+        for (first, second, _) in terminal_graph.minimum_spanning_tree_edges() {
+            let path = trees[first.to_index()]
+                .path_to(T::from_index(terminals[second.to_index()]))
+                .unwrap();
+            for pair in path.windows(2) {
+                let cost = self
+                    .cheapest_edge(pair[0].to_index(), pair[1].to_index())
+                    .unwrap();
+                expanded.add_edge(pair[0], pair[1], cost);
+            }
+        }
+        let mut tree_edges = expanded
+            .minimum_spanning_tree_edges()
+            .into_iter()
+            .map(|(from, to, _)| (from.to_index(), to.to_index()))
+            .collect::<Vec<_>>();
+
+        let mut is_terminal = vec![false; self.vertices];
+        for terminal in &terminals {
+            is_terminal[*terminal] = true;
+        }
+        loop {
+            let mut degree = vec![0; self.vertices];
+            for (from, to) in &tree_edges {
+                degree[*from] += 1;
+                degree[*to] += 1;
+            }
+
+            let before = tree_edges.len();
+            let is_dangling = |node: usize| degree[node] == 1 && !is_terminal[node];
+            tree_edges.retain(|(from, to)| !is_dangling(*from) && !is_dangling(*to));
+            if tree_edges.len() == before {
+                break;
+            }
+        }
+
+        Some(self.steiner_from_edges(tree_edges))
+    }
+
+    /// Optimal Steiner tree by the Dreyfus–Wagner dynamic program, in
+    /// O(3^k |V| + 2^k |E| log |V|) time and O(2^k |V|) memory for `k`
+    /// terminals, so only practical for small terminal sets. `None` when the
+    /// terminals are not connected.
+    pub fn steiner_tree_exact(&self, terminals: &[T]) -> Option<SteinerTree<T, W>> {
+        let terminals = Self::unique_terminals(terminals);
+        if terminals.len() < 2 {
+            return Some(SteinerTree {
+                edges: vec![],
+                cost: W::zero(),
+            });
+        }
+
+        let full_mask = (1usize << terminals.len()) - 1;
+        let mut best = vec![vec![W::max_value(); self.vertices]; full_mask + 1];
+        let mut step = vec![vec![SteinerStep::Unreached; self.vertices]; full_mask + 1];
+
+        for (index, terminal) in terminals.iter().enumerate() {
+            best[1 << index][*terminal] = W::zero();
+            step[1 << index][*terminal] = SteinerStep::Terminal;
+        }
+
+        for mask in 1..=full_mask {
+            // join two smaller trees meeting at the same node
+            let mut submask = (mask - 1) & mask;
+            while submask > 0 {
+                // visit each unordered split once
+                if submask < mask ^ submask {
+                    for node in 0..self.vertices {
+                        let (first, second) = (best[submask][node], best[mask ^ submask][node]);
+                        if first == W::max_value() || second == W::max_value() {
+                            continue;
+                        }
+                        let merged = first.saturating_add(second);
+                        if merged < best[mask][node] {
+                            best[mask][node] = merged;
+                            step[mask][node] = SteinerStep::Merge(submask);
+                        }
+                    }
+                }
+                submask = (submask - 1) & mask;
+            }
+
+            // grow the trees along edges (Dijkstra from every reached node)
+            let mut nodes_q = (0..self.vertices)
+                .filter(|node| best[mask][*node] != W::max_value())
+                .map(|node| (Reverse(TotalOrd(best[mask][node])), node))
+                .collect::<BinaryHeap<_>>();
+            while let Some((Reverse(TotalOrd(cost)), node)) = nodes_q.pop() {
+                if cost > best[mask][node] {
+                    continue;
+                }
+                for edge in &self.adjacencies[node] {
+                    let adj_node = edge.to.to_index();
+                    let new_cost = cost.saturating_add(edge.cost);
+                    if new_cost < best[mask][adj_node] {
+                        best[mask][adj_node] = new_cost;
+                        step[mask][adj_node] = SteinerStep::Extend(node);
+                        nodes_q.push((Reverse(TotalOrd(new_cost)), adj_node));
+                    }
+                }
+            }
+        }
+
+        if best[full_mask][terminals[0]] == W::max_value() {
+            return None;
+        }
+
+        let mut tree_edges = vec![];
+        let mut pending = vec![(full_mask, terminals[0])];
+        while let Some((mask, node)) = pending.pop() {
+            match step[mask][node] {
+                SteinerStep::Unreached | SteinerStep::Terminal => {}
+                SteinerStep::Merge(submask) => {
+                    pending.push((submask, node));
+                    pending.push((mask ^ submask, node));
+                }
+                SteinerStep::Extend(previous) => {
+                    tree_edges.push((previous, node));
+                    pending.push((mask, previous));
+                }
+            }
+        }
+
+        // trees of disjoint terminal sets may share zero cost edges
+        for edge in tree_edges.iter_mut() {
+            *edge = (edge.0.min(edge.1), edge.0.max(edge.1));
+        }
+        tree_edges.sort_unstable();
+        tree_edges.dedup();
+
+        Some(self.steiner_from_edges(tree_edges))
+    }
+}
@@ -0,0 +1,75 @@
+//! Graphs shared by the unit tests: generated ones with many equally short
+//! paths, the bundled `graphs/*.gph`, and a barbell with an obvious split.
+#[cfg(feature = "parallel")]
+use std::collections::VecDeque;
+use std::fs;
+
+use super::{
+    generate::{erdos_renyi_gnp, grid, WeightDistribution},
+    Graph, Numeric,
+};
+
+/// Grids and random graphs of 300 nodes with weights 0..3, so most nodes
+/// have several shortest paths.
+pub(super) fn generated_graphs() -> Vec<Graph<u32, u32>> {
+    let weights = WeightDistribution::UniformInt { low: 0, high: 3 };
+    vec![
+        grid(20, 30, &weights, 1),
+        grid(1, 300, &weights, 2),
+        erdos_renyi_gnp(300, 0.02, &weights, 3),
+        erdos_renyi_gnp(300, 0.005, &weights, 4),
+    ]
+}
+
+/// Every `graphs/*.gph` of the crate with its file name, by name.
+pub(super) fn bundled_graphs<W: Numeric>() -> Vec<(String, Graph<u32, W>)> {
+    let mut paths = fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/graphs"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "gph"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert!(!paths.is_empty(), "No bundled graphs found");
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let name = path.file_name().unwrap().to_str().unwrap().to_string();
+            let graph = Graph::read_from_file(path.to_str().unwrap());
+            (name, graph)
+        })
+        .collect()
+}
+
+/// Two cliques of `clique_size` nodes (`0..clique_size` and the rest) with
+/// unit costs, joined by a single edge.
+pub(super) fn barbell<W: Numeric>(clique_size: u32) -> Graph<u32, W> {
+    let mut graph = Graph::new(2 * clique_size as usize);
+    for first in [0, clique_size] {
+        for from in first..first + clique_size {
+            for to in from + 1..first + clique_size {
+                graph.add_edge(from, to, W::one());
+            }
+        }
+    }
+    graph.add_edge(clique_size - 1, clique_size, W::one());
+    graph
+}
+
+/// Plain sequential BFS depths from `start`, `None` when unreachable.
+#[cfg(feature = "parallel")]
+pub(super) fn bfs_levels<W: Numeric>(graph: &Graph<u32, W>, start: usize) -> Vec<Option<usize>> {
+    let mut levels = vec![None; graph.vertices];
+    levels[start] = Some(0);
+    let mut bfs_queue = VecDeque::from([start]);
+    while let Some(node) = bfs_queue.pop_front() {
+        for edge in &graph.adjacencies[node] {
+            let adj_node = edge.to as usize;
+            if levels[adj_node].is_none() {
+                levels[adj_node] = Some(levels[node].unwrap() + 1);
+                bfs_queue.push_back(adj_node);
+            }
+        }
+    }
+    levels
+}
@@ -0,0 +1,145 @@
+//! BFS and Dijkstra over part of the graph, without building a filtered copy:
+//! nodes and edges can be excluded by predicates, the search cut off at a
+//! depth or cost, and started from several nodes at once (distances are then
+//! to the nearest of them).
+//!
+//! Node filters take the node, edge filters `(from, to, cost)`. Sources that
+//! fail the node filter are skipped like any other node.
+use std::collections::{BinaryHeap, VecDeque};
+
+use super::{relax, Graph, NodeNo, Numeric, PathDistance, SearchState, ShortestPathTree, TotalOrd};
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    /// Number of edges from the nearest source to every node, `None` for
+    /// nodes not reached within `max_depth` edges through allowed nodes and
+    /// edges.
+    pub fn bfs_levels_filtered(
+        &self,
+        sources: &[T],
+        max_depth: Option<usize>,
+        node_filter: impl Fn(T) -> bool,
+        edge_filter: impl Fn(T, T, W) -> bool,
+    ) -> Vec<Option<usize>> {
+        let mut levels = vec![None; self.vertices];
+        let mut bfs_queue = VecDeque::new();
+        for source in sources {
+            if node_filter(*source) && levels[source.to_index()].is_none() {
+                levels[source.to_index()] = Some(0);
+                bfs_queue.push_back(*source);
+            }
+        }
+
+        while let Some(current_node) = bfs_queue.pop_front() {
+            let current_depth = levels[current_node.to_index()].unwrap();
+            if max_depth.is_some_and(|max_depth| current_depth >= max_depth) {
+                continue;
+            }
+
+            for edge in &self.adjacencies[current_node.to_index()] {
+                let adj_node = edge.to;
+                if levels[adj_node.to_index()].is_none()
+                    && node_filter(adj_node)
+                    && edge_filter(current_node, adj_node, edge.cost)
+                {
+                    levels[adj_node.to_index()] = Some(current_depth + 1);
+                    bfs_queue.push_back(adj_node);
+                }
+            }
+        }
+
+        levels
+    }
+
+    /// Dijkstra from the nearest of `sources` through allowed nodes and
+    /// edges, leaving nodes farther than `max_cost` unreached.
+    ///
+    /// Panics when `sources` is empty.
+    pub fn shortest_path_tree_filtered(
+        &self,
+        sources: &[T],
+        max_cost: Option<W>,
+        node_filter: impl Fn(T) -> bool,
+        edge_filter: impl Fn(T, T, W) -> bool,
+    ) -> ShortestPathTree<T, W> {
+        self.filtered_dijkstra(sources, None, max_cost, node_filter, edge_filter)
+    }
+
+    /// Like `shortest_path`, from the nearest of `sources` and through
+    /// allowed nodes and edges only. `None` when `target` can't be reached
+    /// within `max_cost`.
+    ///
+    /// Panics when `sources` is empty.
+    pub fn shortest_path_filtered(
+        &self,
+        sources: &[T],
+        target: T,
+        max_cost: Option<W>,
+        node_filter: impl Fn(T) -> bool,
+        edge_filter: impl Fn(T, T, W) -> bool,
+    ) -> Option<(W, Vec<T>)> {
+        let tree =
+            self.filtered_dijkstra(sources, Some(target), max_cost, node_filter, edge_filter);
+        Some((tree.distance(target)?, tree.path_to(target)?))
+    }
+
+    /// Stops early once `target` is settled.
+    pub(super) fn filtered_dijkstra(
+        &self,
+        sources: &[T],
+        target: Option<T>,
+        max_cost: Option<W>,
+        node_filter: impl Fn(T) -> bool,
+        edge_filter: impl Fn(T, T, W) -> bool,
+    ) -> ShortestPathTree<T, W> {
+        assert!(
+            !sources.is_empty(),
+            "Can't run Dijkstra without a source node"
+        );
+
+        let mut nodes_q: BinaryHeap<SearchState<T, TotalOrd<W>>> = BinaryHeap::new();
+        let mut distance = vec![PathDistance::unreached(sources[0]); self.vertices];
+
+        for source in sources {
+            if node_filter(*source) {
+                distance[source.to_index()] = PathDistance::start(*source);
+                nodes_q.push(SearchState {
+                    node: *source,
+                    key: TotalOrd(W::zero()),
+                });
+            }
+        }
+
+        while let Some(SearchState {
+            node,
+            key: TotalOrd(cost),
+        }) = nodes_q.pop()
+        {
+            // the target's parent is only certain once nothing as cheap is left
+            if target.is_some_and(|target| cost > distance[target.to_index()].cost) {
+                break;
+            }
+            if cost > distance[node.to_index()].cost {
+                continue;
+            }
+
+            for edge in &self.adjacencies[node.to_index()] {
+                let allowed = max_cost.is_none_or(|max_cost| {
+                    distance[node.to_index()].cost.saturating_add(edge.cost) <= max_cost
+                }) && node_filter(edge.to)
+                    && edge_filter(node, edge.to, edge.cost);
+
+                if allowed && relax(&mut distance, node, edge.to, edge.cost) {
+                    nodes_q.push(SearchState {
+                        node: edge.to,
+                        key: TotalOrd(distance[edge.to.to_index()].cost),
+                    });
+                }
+            }
+        }
+
+        ShortestPathTree {
+            sources: sources.to_vec(),
+            distance,
+        }
+    }
+}
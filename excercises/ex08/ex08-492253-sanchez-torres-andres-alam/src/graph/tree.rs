@@ -0,0 +1,276 @@
+//! Rooted spanning trees (forests on disconnected graphs) with binary lifting
+//! for O(log |V|) lowest common ancestor and path queries.
+use std::collections::VecDeque;
+
+use super::{Graph, NodeNo, Numeric, TotalOrd};
+
+/// Every node is either a root or has a parent edge; each connected part is
+/// rooted at its lowest node (or at the start of a BFS tree).
+pub struct Tree<T: NodeNo, W: Numeric> {
+    parent: Vec<Option<(T, W)>>,
+    root: Vec<usize>,
+    depth: Vec<usize>,
+    // cost of the path from the root
+    root_distance: Vec<W>,
+    // ancestors[k][node]: ancestor 2^k levels up, the root once that overshoots
+    ancestors: Vec<Vec<usize>>,
+    // heaviest[k][node]: child end of the most expensive edge among those
+    // 2^k levels up, `None` when there are none
+    heaviest: Vec<Vec<Option<usize>>>,
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    /// The minimum spanning forest of `minimum_spanning_tree_edges` as a
+    /// `Tree`.
+    pub fn minimum_spanning_tree_rooted(&self) -> Tree<T, W> {
+        Tree::from_edges(self.vertices, &self.minimum_spanning_tree_edges())
+    }
+
+    /// BFS tree from `root`, using the edge that first reached each node.
+    /// Unreachable nodes are left as single node trees.
+    pub fn bfs_tree(&self, root: T) -> Tree<T, W> {
+        let mut parent: Vec<Option<(T, W)>> = vec![None; self.vertices];
+        let mut visited = vec![false; self.vertices];
+        let mut order = vec![root.to_index()];
+        visited[root.to_index()] = true;
+
+        let mut bfs_queue = VecDeque::from([root.to_index()]);
+        while let Some(current_node) = bfs_queue.pop_front() {
+            for edge in &self.adjacencies[current_node] {
+                let adj_node = edge.to.to_index();
+                if !visited[adj_node] {
+                    visited[adj_node] = true;
+                    parent[adj_node] = Some((edge.from, edge.cost));
+                    order.push(adj_node);
+                    bfs_queue.push_back(adj_node);
+                }
+            }
+        }
+        order.extend((0..self.vertices).filter(|node| !visited[*node]));
+
+        Tree::from_parents(parent, &order)
+    }
+}
+
+impl<T: NodeNo, W: Numeric> Tree<T, W> {
+    /// Roots the forest given by `edges` over `vertices` nodes. Panics if the
+    /// edges contain a cycle.
+    pub fn from_edges(vertices: usize, edges: &[(T, T, W)]) -> Self {
+        let mut adjacencies = vec![vec![]; vertices];
+        for (from, to, cost) in edges {
+            adjacencies[from.to_index()].push((*to, *cost));
+            adjacencies[to.to_index()].push((*from, *cost));
+        }
+
+        let mut parent: Vec<Option<(T, W)>> = vec![None; vertices];
+        let mut visited = vec![false; vertices];
+        let mut order = vec![];
+        for root in 0..vertices {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            order.push(root);
+
+            let mut bfs_queue = VecDeque::from([root]);
+            while let Some(current_node) = bfs_queue.pop_front() {
+                for (adj_node, cost) in &adjacencies[current_node] {
+                    if parent[current_node].is_some_and(|(parent, _)| parent == *adj_node) {
+                        continue;
+                    }
+                    assert!(
+                        !visited[adj_node.to_index()],
+                        "Can't build a tree from edges with a cycle"
+                    );
+                    visited[adj_node.to_index()] = true;
+                    parent[adj_node.to_index()] = Some((T::from_index(current_node), *cost));
+                    order.push(adj_node.to_index());
+                    bfs_queue.push_back(adj_node.to_index());
+                }
+            }
+        }
+
+        Tree::from_parents(parent, &order)
+    }
+
+    /// `order` lists every node after its parent.
+    fn from_parents(parent: Vec<Option<(T, W)>>, order: &[usize]) -> Self {
+        let vertices = parent.len();
+        let mut root = (0..vertices).collect::<Vec<_>>();
+        let mut depth = vec![0usize; vertices];
+        let mut root_distance = vec![W::zero(); vertices];
+        for &node in order {
+            if let Some((parent_node, cost)) = parent[node] {
+                let parent_node = parent_node.to_index();
+                root[node] = root[parent_node];
+                depth[node] = depth[parent_node] + 1;
+                root_distance[node] = root_distance[parent_node] + cost;
+            }
+        }
+
+        let max_depth = depth.iter().copied().max().unwrap_or(0);
+        let levels = (usize::BITS - max_depth.leading_zeros()).max(1) as usize;
+        let mut ancestors = vec![(0..vertices)
+            .map(|node| parent[node].map_or(node, |(parent, _)| parent.to_index()))
+            .collect::<Vec<_>>()];
+        let mut heaviest = vec![(0..vertices)
+            .map(|node| parent[node].map(|_| node))
+            .collect::<Vec<_>>()];
+
+        let edge_cost = |child: usize| parent[child].unwrap().1;
+        for level in 1..levels {
+            let (previous_ancestors, previous_heaviest) =
+                (&ancestors[level - 1], &heaviest[level - 1]);
+            let next_heaviest = (0..vertices)
+                .map(|node| {
+                    let half = previous_ancestors[node];
+                    match (previous_heaviest[node], previous_heaviest[half]) {
+                        (Some(lower), Some(upper)) => {
+                            Some(if TotalOrd(edge_cost(upper)) > TotalOrd(edge_cost(lower)) {
+                                upper
+                            } else {
+                                lower
+                            })
+                        }
+                        (lower, _) => lower,
+                    }
+                })
+                .collect();
+            let next_ancestors = (0..vertices)
+                .map(|node| previous_ancestors[previous_ancestors[node]])
+                .collect();
+
+            ancestors.push(next_ancestors);
+            heaviest.push(next_heaviest);
+        }
+
+        Tree {
+            parent,
+            root,
+            depth,
+            root_distance,
+            ancestors,
+            heaviest,
+        }
+    }
+
+    pub fn vertices(&self) -> usize {
+        self.parent.len()
+    }
+
+    /// `None` for roots.
+    pub fn parent(&self, node: T) -> Option<T> {
+        self.parent[node.to_index()].map(|(parent, _)| parent)
+    }
+
+    pub fn root_of(&self, node: T) -> T {
+        T::from_index(self.root[node.to_index()])
+    }
+
+    pub fn depth(&self, node: T) -> usize {
+        self.depth[node.to_index()]
+    }
+
+    fn lift(&self, mut node: usize, levels: usize) -> usize {
+        for (level, ancestors) in self.ancestors.iter().enumerate() {
+            if levels & (1 << level) != 0 {
+                node = ancestors[node];
+            }
+        }
+        node
+    }
+
+    /// Lowest common ancestor, `None` when the nodes lie in different trees.
+    pub fn lca(&self, first: T, second: T) -> Option<T> {
+        let (mut first, mut second) = (first.to_index(), second.to_index());
+        if self.root[first] != self.root[second] {
+            return None;
+        }
+
+        if self.depth[first] < self.depth[second] {
+            std::mem::swap(&mut first, &mut second);
+        }
+        first = self.lift(first, self.depth[first] - self.depth[second]);
+        if first == second {
+            return Some(T::from_index(first));
+        }
+
+        for ancestors in self.ancestors.iter().rev() {
+            if ancestors[first] != ancestors[second] {
+                first = ancestors[first];
+                second = ancestors[second];
+            }
+        }
+
+        Some(T::from_index(self.ancestors[0][first]))
+    }
+
+    /// Cost of the tree path between the nodes.
+    pub fn tree_distance(&self, first: T, second: T) -> Option<W> {
+        let lca = self.lca(first, second)?.to_index();
+        let up = |node: T| self.root_distance[node.to_index()] - self.root_distance[lca];
+
+        Some(up(first) + up(second))
+    }
+
+    /// Nodes of the tree path from `first` to `second`.
+    pub fn path(&self, first: T, second: T) -> Option<Vec<T>> {
+        let lca = self.lca(first, second)?;
+
+        let climb = |mut node: T| {
+            let mut nodes = vec![];
+            while node != lca {
+                nodes.push(node);
+                node = self.parent(node).unwrap();
+            }
+            nodes
+        };
+
+        let mut path = climb(first);
+        path.push(lca);
+        path.extend(climb(second).into_iter().rev());
+
+        Some(path)
+    }
+
+    /// Child end of the most expensive edge among the `levels` edges above
+    /// `node`.
+    fn heaviest_above(&self, mut node: usize, levels: usize) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        for (level, ancestors) in self.ancestors.iter().enumerate() {
+            if levels & (1 << level) == 0 {
+                continue;
+            }
+            if let Some(candidate) = self.heaviest[level][node] {
+                if best.is_none_or(|best| {
+                    TotalOrd(self.parent[candidate].unwrap().1)
+                        > TotalOrd(self.parent[best].unwrap().1)
+                }) {
+                    best = Some(candidate);
+                }
+            }
+            node = ancestors[node];
+        }
+        best
+    }
+
+    /// Most expensive edge on the tree path as `(child, parent, cost)`,
+    /// `None` for an empty path or nodes in different trees.
+    pub fn max_edge_on_path(&self, first: T, second: T) -> Option<(T, T, W)> {
+        let lca = self.lca(first, second)?.to_index();
+        let (first, second) = (first.to_index(), second.to_index());
+
+        let candidates = [
+            self.heaviest_above(first, self.depth[first] - self.depth[lca]),
+            self.heaviest_above(second, self.depth[second] - self.depth[lca]),
+        ];
+        candidates
+            .into_iter()
+            .flatten()
+            .map(|child| {
+                let (parent, cost) = self.parent[child].unwrap();
+                (T::from_index(child), parent, cost)
+            })
+            .max_by_key(|(_, _, cost)| TotalOrd(*cost))
+    }
+}
@@ -0,0 +1,367 @@
+//! Travelling salesman heuristics on the metric closure of a graph, i.e. the
+//! complete graph whose edge costs are shortest path distances, so a tour may
+//! pass through a node more than once in the original graph.
+use super::{
+    euler::hierholzer, matching::min_weight_perfect_matching, Graph, NodeNo, Numeric, TotalOrd,
+};
+
+/// Closed tour visiting every node once, returning from the last node to the
+/// first. `cost` is measured on the metric closure.
+pub struct Tour<T: NodeNo, W: Numeric> {
+    pub nodes: Vec<T>,
+    pub cost: W,
+}
+
+pub struct MetricClosure<T: NodeNo, W: Numeric> {
+    distance: Vec<Vec<W>>,
+    mst_edges: Vec<(T, T, W)>,
+}
+
+impl<T: NodeNo, W: Numeric> Graph<T, W> {
+    /// All pairs shortest path distances through one Dijkstra per node,
+    /// O(|V||E| log |V|). `None` when the graph is disconnected, as no tour
+    /// exists then.
+    pub fn metric_closure(&self) -> Option<MetricClosure<T, W>> {
+        let distance = (0..self.vertices)
+            .map(|source| {
+                let tree = self.shortest_path_tree(T::from_index(source));
+                (0..self.vertices)
+                    .map(|target| tree.distance(T::from_index(target)))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(MetricClosure {
+            distance,
+            mst_edges: self.minimum_spanning_tree_edges(),
+        })
+    }
+}
+
+impl<T: NodeNo, W: Numeric> MetricClosure<T, W> {
+    pub fn distance(&self, from: T, to: T) -> W {
+        self.distance[from.to_index()][to.to_index()]
+    }
+
+    fn cost(&self, from: usize, to: usize) -> W {
+        self.distance[from][to]
+    }
+
+    fn tour(&self, order: Vec<usize>) -> Tour<T, W> {
+        let mut cost = W::zero();
+        for index in 0..order.len() {
+            cost += self.cost(order[index], order[(index + 1) % order.len()]);
+        }
+
+        Tour {
+            nodes: order.into_iter().map(T::from_index).collect(),
+            cost,
+        }
+    }
+
+    fn order_of(tour: &Tour<T, W>) -> Vec<usize> {
+        tour.nodes.iter().map(|node| node.to_index()).collect()
+    }
+
+    /// Always moves to the closest unvisited node. O(|V|^2).
+    pub fn nearest_neighbour(&self, start: T) -> Tour<T, W> {
+        let nodes = self.distance.len();
+        if nodes == 0 {
+            return self.tour(vec![]);
+        }
+        let mut visited = vec![false; nodes];
+        let mut order = vec![start.to_index()];
+        visited[start.to_index()] = true;
+
+        while order.len() < nodes {
+            let current = *order.last().unwrap();
+            let next = (0..nodes)
+                .filter(|node| !visited[*node])
+                .min_by_key(|node| TotalOrd(self.cost(current, *node)))
+                .unwrap();
+            visited[next] = true;
+            order.push(next);
+        }
+
+        self.tour(order)
+    }
+
+    /// Visits nodes in DFS preorder of the minimum spanning tree. With
+    /// non-negative costs the tour costs at most twice the optimum.
+    pub fn mst_preorder(&self) -> Tour<T, W> {
+        let nodes = self.distance.len();
+        if nodes == 0 {
+            return self.tour(vec![]);
+        }
+        let mut tree = vec![vec![]; nodes];
+        for (from, to, _) in &self.mst_edges {
+            tree[from.to_index()].push(to.to_index());
+            tree[to.to_index()].push(from.to_index());
+        }
+
+        let mut visited = vec![false; nodes];
+        let mut order = vec![];
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            if visited[node] {
+                continue;
+            }
+            visited[node] = true;
+            order.push(node);
+            // reversed so children are visited in increasing order
+            stack.extend(tree[node].iter().rev().filter(|child| !visited[**child]));
+        }
+
+        self.tour(order)
+    }
+
+    /// Christofides: joins the odd degree nodes of the minimum spanning tree
+    /// with a minimum weight perfect matching (exact for up to 20 odd nodes,
+    /// greedy with local improvement above that), then shortcuts an Euler
+    /// circuit of the resulting multigraph.
+    pub fn christofides(&self) -> Tour<T, W> {
+        let nodes = self.distance.len();
+        if nodes == 0 {
+            return self.tour(vec![]);
+        }
+        let mut edges = self
+            .mst_edges
+            .iter()
+            .map(|(from, to, _)| (from.to_index(), to.to_index()))
+            .collect::<Vec<_>>();
+
+        let mut degree = vec![0; nodes];
+        for (from, to) in &edges {
+            degree[*from] += 1;
+            degree[*to] += 1;
+        }
+        let odd_nodes = (0..nodes)
+            .filter(|node| degree[*node] % 2 == 1)
+            .collect::<Vec<_>>();
+
+        let matching = min_weight_perfect_matching(odd_nodes.len(), |first, second| {
+            self.cost(odd_nodes[first], odd_nodes[second])
+        });
+        edges.extend(
+            matching
+                .into_iter()
+                .map(|(first, second)| (odd_nodes[first], odd_nodes[second])),
+        );
+
+        let mut visited = vec![false; nodes];
+        let circuit = std::iter::once(0).chain(
+            hierholzer(nodes, &edges, 0)
+                .into_iter()
+                .map(|(_, to, _)| to),
+        );
+        let order = circuit
+            .filter(|node| !std::mem::replace(&mut visited[*node], true))
+            .collect();
+
+        self.tour(order)
+    }
+
+    /// Reverses tour segments while that shortens the tour.
+    pub fn two_opt(&self, tour: &Tour<T, W>) -> Tour<T, W> {
+        let mut order = Self::order_of(tour);
+        let nodes = order.len();
+
+        let mut improved = nodes > 3;
+        while improved {
+            improved = false;
+            for i in 0..nodes - 1 {
+                for j in i + 2..nodes {
+                    let (a, b) = (order[i], order[i + 1]);
+                    let (c, d) = (order[j], order[(j + 1) % nodes]);
+                    if a == d {
+                        continue;
+                    }
+
+                    let current = self.cost(a, b).saturating_add(self.cost(c, d));
+                    let swapped = self.cost(a, c).saturating_add(self.cost(b, d));
+                    if TotalOrd(swapped) < TotalOrd(current) {
+                        order[i + 1..=j].reverse();
+                        improved = true;
+                    }
+                }
+            }
+        }
+
+        self.tour(order)
+    }
+
+    /// Moves segments of up to three consecutive nodes, possibly reversed, to
+    /// another position in the tour while that shortens it.
+    pub fn or_opt(&self, tour: &Tour<T, W>) -> Tour<T, W> {
+        let mut order = Self::order_of(tour);
+        let nodes = order.len();
+
+        let mut improved = nodes > 3;
+        while improved {
+            improved = false;
+            'search: for segment_len in 1..=3.min(nodes - 2) {
+                for start in 0..nodes {
+                    let end = (start + segment_len - 1) % nodes;
+                    let prev = order[(start + nodes - 1) % nodes];
+                    let next = order[(end + 1) % nodes];
+                    let (first, last) = (order[start], order[end]);
+
+                    let removed = self.cost(prev, first).saturating_add(self.cost(last, next));
+                    let bridged = self.cost(prev, next);
+
+                    // rotate so the segment sits at the end, leaving the rest as a path
+                    let mut rotated = order.clone();
+                    rotated.rotate_left((end + 1) % nodes);
+                    let rest = &rotated[..nodes - segment_len];
+
+                    for gap in 0..rest.len() - 1 {
+                        let (x, y) = (rest[gap], rest[gap + 1]);
+                        let cut = removed.saturating_add(self.cost(x, y));
+
+                        for reversed in [false, true] {
+                            let (head, tail) = if reversed {
+                                (last, first)
+                            } else {
+                                (first, last)
+                            };
+                            let added = bridged
+                                .saturating_add(self.cost(x, head))
+                                .saturating_add(self.cost(tail, y));
+
+                            if TotalOrd(added) < TotalOrd(cut) {
+                                let mut segment = rotated[nodes - segment_len..].to_vec();
+                                if reversed {
+                                    segment.reverse();
+                                }
+                                let mut next_order = rest[..=gap].to_vec();
+                                next_order.extend(segment);
+                                next_order.extend(&rest[gap + 1..]);
+
+                                order = next_order;
+                                improved = true;
+                                break 'search;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        self.tour(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::generate::{erdos_renyi_gnp, WeightDistribution},
+        *,
+    };
+
+    /// Connected random graphs, from sparse (few shortcuts in the closure)
+    /// to complete.
+    fn connected_graphs(vertices: usize, count: u64) -> Vec<Graph<u32, u32>> {
+        let weights = WeightDistribution::UniformInt { low: 0, high: 20 };
+        (0..count * 4)
+            .map(|seed| {
+                erdos_renyi_gnp(
+                    vertices,
+                    [0.3, 0.5, 0.8, 1.0][seed as usize % 4],
+                    &weights,
+                    seed,
+                )
+            })
+            .filter(|graph| graph.connected_components() == 1)
+            .take(count as usize)
+            .collect()
+    }
+
+    /// Cheapest tour by trying every order starting at node 0.
+    fn optimal_cost(closure: &MetricClosure<u32, u32>) -> u32 {
+        fn search(
+            closure: &MetricClosure<u32, u32>,
+            order: &mut Vec<usize>,
+            left: &mut Vec<usize>,
+        ) -> u32 {
+            if left.is_empty() {
+                return closure.tour(order.clone()).cost;
+            }
+            let mut best = u32::MAX;
+            for index in 0..left.len() {
+                let node = left.swap_remove(index);
+                order.push(node);
+                best = best.min(search(closure, order, left));
+                order.pop();
+                left.push(node);
+                let last = left.len() - 1;
+                left.swap(index, last);
+            }
+            best
+        }
+
+        let mut left = (1..closure.distance.len()).collect();
+        search(closure, &mut vec![0], &mut left)
+    }
+
+    fn assert_valid_tour(closure: &MetricClosure<u32, u32>, tour: &Tour<u32, u32>) {
+        let mut nodes = tour.nodes.clone();
+        nodes.sort_unstable();
+        assert_eq!(
+            nodes,
+            (0..closure.distance.len() as u32).collect::<Vec<_>>()
+        );
+        assert_eq!(tour.cost, closure.tour(MetricClosure::order_of(tour)).cost);
+    }
+
+    #[test]
+    fn empty_graph_has_empty_tours() {
+        let closure = Graph::<u32, u32>::new(0).metric_closure().unwrap();
+        assert!(closure.nearest_neighbour(0).nodes.is_empty());
+        assert!(closure.mst_preorder().nodes.is_empty());
+        assert!(closure.christofides().nodes.is_empty());
+    }
+
+    #[test]
+    fn christofides_is_within_one_and_a_half_of_optimal() {
+        for vertices in 2..=8 {
+            for graph in connected_graphs(vertices, 12) {
+                let closure = graph.metric_closure().unwrap();
+                let optimal = optimal_cost(&closure);
+                let tour = closure.christofides();
+                assert_valid_tour(&closure, &tour);
+                assert!(
+                    2 * tour.cost <= 3 * optimal,
+                    "christofides {} vs optimal {optimal} on {vertices} nodes",
+                    tour.cost
+                );
+                assert!(closure.mst_preorder().cost <= 2 * optimal);
+            }
+        }
+    }
+
+    #[test]
+    fn local_search_never_increases_cost() {
+        let mut rng = super::super::rng::Rng::new(7);
+        for vertices in [4, 5, 9, 30] {
+            for graph in connected_graphs(vertices, 8) {
+                let closure = graph.metric_closure().unwrap();
+                let mut shuffled = (0..vertices).collect::<Vec<_>>();
+                rng.shuffle(&mut shuffled);
+
+                let starts = [
+                    closure.nearest_neighbour(vertices as u32 - 1),
+                    closure.mst_preorder(),
+                    closure.christofides(),
+                    closure.tour(shuffled),
+                ];
+                for start in &starts {
+                    assert_valid_tour(&closure, start);
+                    for improved in [closure.two_opt(start), closure.or_opt(start)] {
+                        assert_valid_tour(&closure, &improved);
+                        assert!(improved.cost <= start.cost);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,2 @@
+//! Andres Alam Sanchez Torres 492253
+pub mod graph;
@@ -1,15 +1,24 @@
 //! Andres Alam Sanchez Torres 492253
-use std::{
-    env,
-    path::Path,
-    time::Instant,
-};
+use std::{env, path::Path, time::Instant};
 
-mod graph;
+use ex06_492253_sanchez_torres_andres_alam::graph::{Graph, NodeNo, Numeric};
 
-use graph::{Graph, NodeNo, Numeric};
+/// Loads the graph and, when a `.names` file sits next to it, its node names.
+fn load_graph<T: NodeNo, W: Numeric>(graph_path: &str) -> Graph<T, W> {
+    let mut graph: Graph<T, W> = Graph::read_from_file(graph_path);
 
-fn test_cholesky<T: NodeNo, W: Numeric>(graph_path: &String, start_node: &T, target_node: &T) {
+    let names_path = Path::new(graph_path).with_extension("names");
+    if names_path.exists() {
+        let names_path = names_path.to_str().unwrap();
+        graph
+            .try_read_node_names(names_path)
+            .unwrap_or_else(|error| panic!("Can't load node names {}: {}", names_path, error));
+    }
+
+    graph
+}
+
+fn test_cholesky<T: NodeNo, W: Numeric>(graph_path: &str, start_label: &str, target_label: &str) {
     let start_time = Instant::now();
 
     let graph_id = Path::new(&graph_path)
@@ -18,16 +27,22 @@ fn test_cholesky<T: NodeNo, W: Numeric>(graph_path: &String, start_node: &T, tar
         .to_str()
         .unwrap();
 
-    let graph: Graph<T, W> = Graph::read_from_file(&graph_path);
+    let graph: Graph<T, W> = load_graph(graph_path);
+    let start_node = graph
+        .node_from_label(start_label)
+        .unwrap_or_else(|| panic!("Can't find start node {}", start_label));
+    let target_node = graph
+        .node_from_label(target_label)
+        .unwrap_or_else(|| panic!("Can't find target node {}", target_label));
 
     let mst = graph.minimum_spanning_tree();
 
     let (shortest_path_len, shortest_path) = graph
-        .shortest_path(*start_node, *target_node)
+        .shortest_path(start_node, target_node)
         .expect("No shortest path exists between source and target node");
     let shortest_path_str = shortest_path
         .iter()
-        .map(|node| (*node + T::one()).to_string())
+        .map(|node| graph.label_of(*node))
         .collect::<Vec<_>>()
         .join(" ");
 
@@ -39,27 +54,33 @@ fn test_cholesky<T: NodeNo, W: Numeric>(graph_path: &String, start_node: &T, tar
     );
 }
 
+fn print_sensitivity<T: NodeNo, W: Numeric>(graph_path: &str) {
+    let graph: Graph<T, W> = load_graph(graph_path);
+
+    print!("{}", graph.mst_sensitivity());
+    match graph.second_best_mst() {
+        Some(second_best) => println!("Second best MST= {}", second_best.cost),
+        None => println!("Second best MST= none (the graph is a forest)"),
+    }
+}
+
 fn main() {
     let mut args = env::args();
     let graph_path = args.nth(1).expect("Can't get graph path from args");
-    let start_node: usize = args
-        .next()
-        .expect("Can't get start node from args")
-        .parse()
-        .expect("Can't parse start node value");
-    let start_node = start_node - 1;
-    let target_node: usize = args
-        .next()
-        .expect("Can't get target node from args")
-        .parse()
-        .expect("Can't parse target node value");
-    let target_node = target_node - 1;
-
-    test_cholesky::<u16, u32>(&graph_path, &(start_node as u16), &(target_node as u16));
-    test_cholesky::<u32, u32>(&graph_path, &(start_node as u32), &(target_node as u32));
-    test_cholesky::<u64, u32>(&graph_path, &(start_node as u64), &(target_node as u64));
-
-    test_cholesky::<u16, f64>(&graph_path, &(start_node as u16), &(target_node as u16));
-    test_cholesky::<u32, f64>(&graph_path, &(start_node as u32), &(target_node as u32));
-    test_cholesky::<u64, f64>(&graph_path, &(start_node as u64), &(target_node as u64));
+    // a node name (from the graph's .names file) or a 1-based id
+    let start_label = args.next().expect("Can't get start node from args");
+    let target_label = args.next().expect("Can't get target node from args");
+    let sensitivity = args.next().is_some_and(|flag| flag == "--sensitivity");
+
+    test_cholesky::<u16, u32>(&graph_path, &start_label, &target_label);
+    test_cholesky::<u32, u32>(&graph_path, &start_label, &target_label);
+    test_cholesky::<u64, u32>(&graph_path, &start_label, &target_label);
+
+    test_cholesky::<u16, f64>(&graph_path, &start_label, &target_label);
+    test_cholesky::<u32, f64>(&graph_path, &start_label, &target_label);
+    test_cholesky::<u64, f64>(&graph_path, &start_label, &target_label);
+
+    if sensitivity {
+        print_sensitivity::<u32, f64>(&graph_path);
+    }
 }